@@ -0,0 +1,3 @@
+pub mod secret_project;
+
+pub use secret_project::*;
@@ -1,6 +1,4 @@
-pub mod secret_project;
-
-use secret_project::*;
+use vertex::*;
 
 fn main() {
     App::new()
@@ -21,18 +19,21 @@ fn main() {
         .add_plugins(CursorPlugin)
         .add_plugins(ParticlePlugin)
         .add_plugins(PuzzleMessagePlugin)
+        .add_plugins(HistoryPlugin)
         .add_plugins(RevealedTextPlugin)
         .add_plugins(UiPlugin)
         .add_plugins(PuzzlePlugin)
+        .add_plugins(SvgExportPlugin)
         .add_plugins(AutoSolverPlugin)
         .add_plugins(ConfettiPlugin)
         .add_plugins(NetworkPlugin)
+        .add_plugins(OptionsPlugin)
         .add_systems(Startup, startup.in_set(MainStartup))
         .add_systems(
             Update,
             (
                 draw_cursor_line,
-                open_puzzle_by_id,
+                open_puzzle_by_key,
                 update_puzzle_mesh,
                 log_app_state_transitions,
                 log_in_editor_state_transitions,
@@ -42,6 +43,7 @@ fn main() {
         .insert_state(AppState::default())
         .add_computed_state::<InEditorOrPlaying>()
         .add_computed_state::<VictoryScreen>()
+        .add_computed_state::<PauseScreen>()
         // `InputFocus` must be set for accessibility to recognize the button.
         .init_resource::<InputFocus>()
         .run();
@@ -50,36 +52,48 @@ fn main() {
 fn startup(
     mut commands: Commands,
     mut _windows: Query<&mut Window, With<PrimaryWindow>>,
-    mut loading: ResMut<NextState<AppState>>,
+    mut state: ResMut<NextState<AppState>>,
 ) {
     commands.spawn(Camera2d);
 
     let args: Vec<String> = std::env::args().collect();
 
-    let home_dir = std::env::home_dir().expect("Expected home directory");
-    let default = home_dir.join(".vertex_install");
-
     let install_dir = match args.get(1) {
         Some(s) => PathBuf::from(s),
-        None => default,
+        None => Installation::default_root(),
     };
 
     let install = Installation::initialize(install_dir).expect("Failed to initialize installation");
 
-    commands.insert_resource(Settings::default());
+    let settings = match Settings::from_file(&install.settings()) {
+        Ok(settings) => settings,
+        Err(e) => {
+            error!("Failed to load settings, using defaults: {:?}", e);
+            Settings::default()
+        }
+    };
+
+    commands.insert_resource(settings);
     commands.insert_resource(install.clone());
     commands.insert_resource(ClearColor(Srgba::new(0.9, 0.9, 0.9, 1.0).into()));
 
     commands.insert_resource(SaveData::default());
     commands.spawn(Puzzle::default());
 
-    commands.insert_resource(
-        Manifest::from_file(&install.network_manifest()).unwrap_or(Manifest::default()),
-    );
+    let packs = match PuzzlePacks::from_install(&install) {
+        Ok(packs) => packs,
+        Err(e) => {
+            error!("Failed to load manifest: {}", e);
+            PuzzlePacks::default()
+        }
+    };
+    commands.insert_resource(packs);
 
+    // whatever is already installed is playable straight away; the sync
+    // runs in the background and updates the manifest when it finishes
     commands.write_message(NetworkFetch);
 
-    loading.set(AppState::Loading);
+    state.set(AppState::Menu);
 }
 
 fn enable_debug_view(state: Res<State<AppState>>, mut fps: ResMut<FpsOverlayConfig>) {
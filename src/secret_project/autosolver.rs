@@ -13,6 +13,7 @@ impl Plugin for AutoSolverPlugin {
 pub struct Autosolver {
     timer: Timer,
     enabled: bool,
+    plan: Option<VecDeque<Deduction>>,
 }
 
 impl Autosolver {
@@ -20,11 +21,13 @@ impl Autosolver {
         Self {
             timer: Timer::from_seconds(0.1, TimerMode::Repeating),
             enabled: false,
+            plan: None,
         }
     }
 
     pub fn toggle(&mut self) {
         self.enabled = !self.enabled;
+        self.plan = None;
     }
 }
 
@@ -32,6 +35,24 @@ fn setup(mut commands: Commands) {
     commands.insert_resource(Autosolver::new());
 }
 
+fn plan_deductions(commands: &mut Commands, puzzle: &Puzzle) -> VecDeque<Deduction> {
+    let result = solve_from_hints(puzzle.hints());
+    let n = result.deductions.len();
+
+    for (i, d) in result.deductions.iter().enumerate() {
+        info!("Deduction {}: {}", i + 1, d);
+    }
+
+    let summary = match result.outcome {
+        SolverOutcome::Solved => format!("Solved in {} deductions", n),
+        SolverOutcome::Stuck => format!("Stuck after {} deductions", n),
+        SolverOutcome::Contradiction => format!("Hints contradict after {} deductions", n),
+    };
+    commands.write_message(TextMessage::debug(summary));
+
+    result.deductions.into()
+}
+
 fn do_autosolver(
     mut commands: Commands,
     mut solver: ResMut<Autosolver>,
@@ -44,31 +65,43 @@ fn do_autosolver(
     }
 
     let dt = time.delta();
-    let mut edges = HashSet::new();
 
     solver.timer.tick(dt);
     if !solver.timer.just_finished() {
         return;
     }
 
-    for (a, b) in &puzzle.solution_edges.0 {
-        if !save.edges.is_edge(*a, *b) && !edges.contains(&(*a, *b)) {
-            edges.insert((*a, *b));
-        }
+    if solver.plan.is_none() {
+        solver.plan = Some(plan_deductions(&mut commands, &puzzle));
+    }
+
+    let mut applied = 0;
 
-        if edges.len() > 20 {
-            break;
+    if let Some(plan) = &mut solver.plan {
+        while applied < 5 {
+            let d = match plan.pop_front() {
+                Some(d) => d,
+                None => break,
+            };
+
+            let (a, b) = d.edge;
+            let is_edge = save.edges.is_edge(a, b);
+            if d.include && !is_edge {
+                commands.write_message(AddEdge(a, b));
+                applied += 1;
+            } else if !d.include && is_edge {
+                commands.write_message(DeleteEdge(a, b));
+                applied += 1;
+            }
         }
     }
 
-    if edges.is_empty() {
+    if applied == 0 {
         commands.write_message(TextMessage::debug("Done!"));
         solver.enabled = false;
+        solver.plan = None;
         return;
     }
 
-    for (a, b) in edges {
-        commands.write_message(ToggleEdge(a, b));
-    }
     commands.write_message(SoundEffect::LightPop);
 }
@@ -0,0 +1,339 @@
+use crate::secret_project::*;
+
+use std::collections::BTreeMap;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Sequence,
+)]
+pub enum Action {
+    AddVertex,
+    Quantize,
+    HideVertices,
+    ClearSelection,
+    SelectAll,
+    SavePuzzle,
+    Undo,
+    Redo,
+    NudgeLeft,
+    NudgeRight,
+    NudgeUp,
+    NudgeDown,
+    ZoomIn,
+    ZoomOut,
+    Ripple,
+    GridParticle,
+    LeaveLoading,
+    LoadingText,
+    Pause,
+    ExportPicture,
+}
+
+impl Action {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::AddVertex => "Add Vertex",
+            Action::Quantize => "Quantize Colors",
+            Action::HideVertices => "Hide Vertices (hold)",
+            Action::ClearSelection => "Clear Selection",
+            Action::SelectAll => "Select All",
+            Action::SavePuzzle => "Save Puzzle",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::NudgeLeft => "Nudge Left",
+            Action::NudgeRight => "Nudge Right",
+            Action::NudgeUp => "Nudge Up",
+            Action::NudgeDown => "Nudge Down",
+            Action::ZoomIn => "Zoom In",
+            Action::ZoomOut => "Zoom Out",
+            Action::Ripple => "Ripple",
+            Action::GridParticle => "Grid Particle",
+            Action::LeaveLoading => "Skip Loading Screen",
+            Action::LoadingText => "Loading Screen Test Text",
+            Action::Pause => "Pause Menu",
+            Action::ExportPicture => "Export Picture",
+        }
+    }
+
+    fn default_binding(&self) -> Binding {
+        let key = |key| Binding::new(key);
+        match self {
+            Action::AddVertex => key(KeyCode::KeyQ),
+            Action::Quantize => key(KeyCode::KeyQ).ctrl(),
+            Action::HideVertices => key(KeyCode::KeyV),
+            Action::ClearSelection => key(KeyCode::KeyC),
+            Action::SelectAll => key(KeyCode::KeyA).ctrl(),
+            Action::SavePuzzle => key(KeyCode::KeyS).ctrl(),
+            Action::Undo => key(KeyCode::KeyZ).ctrl(),
+            Action::Redo => key(KeyCode::KeyZ).ctrl().shift(),
+            Action::NudgeLeft => key(KeyCode::ArrowLeft),
+            Action::NudgeRight => key(KeyCode::ArrowRight),
+            Action::NudgeUp => key(KeyCode::ArrowUp),
+            Action::NudgeDown => key(KeyCode::ArrowDown),
+            Action::ZoomIn => key(KeyCode::Equal),
+            Action::ZoomOut => key(KeyCode::Minus),
+            Action::Ripple => key(KeyCode::KeyF),
+            Action::GridParticle => key(KeyCode::KeyG),
+            Action::LeaveLoading => key(KeyCode::KeyU),
+            Action::LoadingText => key(KeyCode::KeyR),
+            Action::Pause => key(KeyCode::Escape),
+            Action::ExportPicture => key(KeyCode::KeyE).ctrl(),
+        }
+    }
+}
+
+// keys which can be bound, by the names used in the settings file
+const BINDABLE_KEYS: &[(&str, KeyCode)] = &[
+    ("A", KeyCode::KeyA),
+    ("B", KeyCode::KeyB),
+    ("C", KeyCode::KeyC),
+    ("D", KeyCode::KeyD),
+    ("E", KeyCode::KeyE),
+    ("F", KeyCode::KeyF),
+    ("G", KeyCode::KeyG),
+    ("H", KeyCode::KeyH),
+    ("I", KeyCode::KeyI),
+    ("J", KeyCode::KeyJ),
+    ("K", KeyCode::KeyK),
+    ("L", KeyCode::KeyL),
+    ("M", KeyCode::KeyM),
+    ("N", KeyCode::KeyN),
+    ("O", KeyCode::KeyO),
+    ("P", KeyCode::KeyP),
+    ("Q", KeyCode::KeyQ),
+    ("R", KeyCode::KeyR),
+    ("S", KeyCode::KeyS),
+    ("T", KeyCode::KeyT),
+    ("U", KeyCode::KeyU),
+    ("V", KeyCode::KeyV),
+    ("W", KeyCode::KeyW),
+    ("X", KeyCode::KeyX),
+    ("Y", KeyCode::KeyY),
+    ("Z", KeyCode::KeyZ),
+    ("0", KeyCode::Digit0),
+    ("1", KeyCode::Digit1),
+    ("2", KeyCode::Digit2),
+    ("3", KeyCode::Digit3),
+    ("4", KeyCode::Digit4),
+    ("5", KeyCode::Digit5),
+    ("6", KeyCode::Digit6),
+    ("7", KeyCode::Digit7),
+    ("8", KeyCode::Digit8),
+    ("9", KeyCode::Digit9),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("Left", KeyCode::ArrowLeft),
+    ("Right", KeyCode::ArrowRight),
+    ("Up", KeyCode::ArrowUp),
+    ("Down", KeyCode::ArrowDown),
+    ("Minus", KeyCode::Minus),
+    ("Equal", KeyCode::Equal),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Escape", KeyCode::Escape),
+];
+
+pub fn is_bindable(key: KeyCode) -> bool {
+    BINDABLE_KEYS.iter().any(|(_, k)| *k == key)
+}
+
+fn ctrl_held(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+}
+
+fn shift_held(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+// a key plus the modifiers which must be held with it. stored in the
+// settings file as text, like "Ctrl+Shift+Z"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Binding {
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+}
+
+impl Binding {
+    pub fn new(key: KeyCode) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            shift: false,
+        }
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    // the binding for a key pressed with whatever modifiers are held now
+    pub fn from_input(key: KeyCode, keys: &ButtonInput<KeyCode>) -> Self {
+        Self {
+            key,
+            ctrl: ctrl_held(keys),
+            shift: shift_held(keys),
+        }
+    }
+
+    fn modifiers_match(&self, keys: &ButtonInput<KeyCode>) -> bool {
+        self.ctrl == ctrl_held(keys) && self.shift == shift_held(keys)
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        match BINDABLE_KEYS.iter().find(|(_, k)| *k == self.key) {
+            Some((name, _)) => write!(f, "{}", name),
+            None => write!(f, "{:?}", self.key),
+        }
+    }
+}
+
+impl From<Binding> for String {
+    fn from(value: Binding) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut binding = Binding::new(KeyCode::Escape);
+        let mut key = None;
+        for part in value.split('+').map(str::trim) {
+            match part.to_lowercase().as_str() {
+                "ctrl" => binding.ctrl = true,
+                "shift" => binding.shift = true,
+                _ => {
+                    let found = BINDABLE_KEYS
+                        .iter()
+                        .find(|(name, _)| name.eq_ignore_ascii_case(part));
+                    match found {
+                        Some((_, k)) if key.is_none() => key = Some(*k),
+                        _ => return Err(format!("Invalid key binding \"{}\"", value)),
+                    }
+                }
+            }
+        }
+        binding.key = key.ok_or(format!("No key in binding \"{}\"", value))?;
+        Ok(binding)
+    }
+}
+
+// actions missing from the settings file use their default bindings
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct KeyBindings(pub BTreeMap<Action, Binding>);
+
+impl KeyBindings {
+    pub fn get(&self, action: Action) -> Binding {
+        self.0
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_binding())
+    }
+
+    pub fn set(&mut self, action: Action, binding: Binding) {
+        self.0.insert(action, binding);
+    }
+
+    pub fn reset(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn just_pressed(&self, action: Action, keys: &ButtonInput<KeyCode>) -> bool {
+        let b = self.get(action);
+        keys.just_pressed(b.key) && b.modifiers_match(keys)
+    }
+
+    pub fn pressed(&self, action: Action, keys: &ButtonInput<KeyCode>) -> bool {
+        let b = self.get(action);
+        keys.pressed(b.key) && b.modifiers_match(keys)
+    }
+
+    // for actions like nudging, where a held modifier changes how far they
+    // go rather than which action it is
+    pub fn key_just_pressed(&self, action: Action, keys: &ButtonInput<KeyCode>) -> bool {
+        keys.just_pressed(self.get(action).key)
+    }
+
+    // actions which share a binding with some other action
+    pub fn conflicts(&self) -> HashSet<Action> {
+        let mut by_binding: HashMap<Binding, Vec<Action>> = HashMap::new();
+        for action in all::<Action>() {
+            by_binding.entry(self.get(action)).or_default().push(action);
+        }
+        by_binding
+            .into_values()
+            .filter(|actions| actions.len() > 1)
+            .flatten()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_dont_conflict() {
+        assert!(KeyBindings::default().conflicts().is_empty());
+    }
+
+    #[test]
+    fn detects_conflicts() {
+        let mut bindings = KeyBindings::default();
+        bindings.set(Action::Undo, Binding::new(KeyCode::KeyQ));
+        let conflicts = bindings.conflicts();
+        assert_eq!(conflicts.len(), 2);
+        assert!(conflicts.contains(&Action::Undo));
+        assert!(conflicts.contains(&Action::AddVertex));
+    }
+
+    #[test]
+    fn bindings_round_trip_as_text() {
+        for action in all::<Action>() {
+            let b = action.default_binding();
+            assert_eq!(Binding::try_from(b.to_string()), Ok(b));
+        }
+        let b = Binding::try_from("ctrl+shift+z".to_string()).unwrap();
+        assert_eq!(b, Binding::new(KeyCode::KeyZ).ctrl().shift());
+        assert!(Binding::try_from("Ctrl+Shift".to_string()).is_err());
+    }
+
+    #[test]
+    fn missing_actions_use_defaults() {
+        let bindings: KeyBindings = serde_yaml::from_str("Undo: Ctrl+U").unwrap();
+        assert_eq!(
+            bindings.get(Action::Undo),
+            Binding::new(KeyCode::KeyU).ctrl()
+        );
+        assert_eq!(bindings.get(Action::Redo), Action::Redo.default_binding());
+    }
+}
@@ -30,9 +30,9 @@ fn on_input_tick(
     keys: Res<ButtonInput<KeyCode>>,
     window: Single<&Window, With<PrimaryWindow>>,
     mut cursor: ResMut<CursorState>,
-    mut puzzle: Single<&mut Puzzle>,
     camera: Single<(&Camera, &GlobalTransform)>,
     app: Res<Settings>,
+    state: Res<State<AppState>>,
 ) {
     let (camera, camera_transform) = *camera;
 
@@ -46,21 +46,29 @@ fn on_input_tick(
         cursor.mouse_pos = None;
     }
 
-    // keyboard presses
-    if keys.just_pressed(KeyCode::KeyQ) {
-        if keys.pressed(KeyCode::ControlLeft) {
-            commands.write_message(Quantize(app.n_colors));
-            commands.write_message(SoundEffect::UiThreePop);
-        } else {
-            if let Some(p) = cursor.get() {
-                puzzle.add_point(p);
-                commands.write_message(SoundEffect::LightPop);
-            }
+    // keyboard presses; on the main menu, typing goes to the search box,
+    // and while paused the puzzle is left alone
+    let suspended = state.is_menu() || state.is_paused();
+
+    if !suspended && app.bindings.just_pressed(Action::Quantize, &keys) {
+        commands.write_message(Quantize(app.n_colors));
+        commands.write_message(SoundEffect::UiThreePop);
+    }
+
+    if !suspended && app.bindings.just_pressed(Action::AddVertex, &keys) {
+        if let Some(p) = cursor.get() {
+            commands.write_message(AddVertex(p));
+            commands.write_message(SoundEffect::LightPop);
         }
     }
 
-    if keys.just_pressed(KeyCode::Escape) {
-        commands.write_message(AppExit::Success);
+    if app.bindings.just_pressed(Action::Pause, &keys) {
+        commands.write_message(UiMessage::Pause);
+    }
+
+    // the picture as the player has it, so a solved puzzle comes out whole
+    if !state.is_menu() && app.bindings.just_pressed(Action::ExportPicture, &keys) {
+        commands.write_message(ExportSvg(SvgMode::Progress));
     }
 }
 
@@ -97,18 +105,12 @@ fn on_keys(
     keys: Res<ButtonInput<KeyCode>>,
     mut mouse_wheel: MessageReader<MouseWheel>,
     mut query: Query<&mut CameraController>,
+    app: Res<Settings>,
 ) {
     // let speed = 1400.0;
     let zoom_speed = 5.0;
 
-    let is_key = |key: KeyCode| {
-        for ex in [KeyCode::ControlLeft, KeyCode::ControlRight] {
-            if keys.pressed(ex) {
-                return false;
-            }
-        }
-        keys.pressed(key)
-    };
+    let is_key = |action: Action| app.bindings.pressed(action, &keys);
 
     for mut ctrl in &mut query {
         // ctrl.linear_vel.y = if is_key(KeyCode::KeyW) {
@@ -127,9 +129,9 @@ fn on_keys(
         //     ctrl.linear_vel.x
         // };
 
-        ctrl.zoom_vel = if is_key(KeyCode::Minus) {
+        ctrl.zoom_vel = if is_key(Action::ZoomOut) {
             zoom_speed
-        } else if is_key(KeyCode::Equal) {
+        } else if is_key(Action::ZoomIn) {
             -zoom_speed
         } else {
             ctrl.zoom_vel
@@ -10,9 +10,7 @@ impl Plugin for CursorPlugin {
                 Update,
                 (
                     draw_mouse_cursor,
-                    draw_selected_vertices.run_if(in_state(AppState::Editing {
-                        mode: EditorMode::Select,
-                    })),
+                    draw_selected_vertices.run_if(uses_selection),
                     collect_selected_vertices.run_if(in_state(AppState::Editing {
                         mode: EditorMode::Select,
                     })),
@@ -22,14 +20,13 @@ impl Plugin for CursorPlugin {
                     do_select.run_if(in_state(AppState::Editing {
                         mode: EditorMode::Select,
                     })),
+                    do_move.run_if(in_state(AppState::Editing {
+                        mode: EditorMode::Move,
+                    })),
+                    nudge_vertices.run_if(is_editor),
                     delete_edges_on_right_click.run_if(is_playing),
+                    clear_selection_on_exit,
                 ),
-            )
-            .add_systems(
-                OnExit(AppState::Editing {
-                    mode: EditorMode::Select,
-                }),
-                on_select_exit,
             );
     }
 }
@@ -84,20 +81,39 @@ pub fn draw_selected_vertices(
     }
 }
 
-pub fn on_select_exit(mut v: ResMut<SelectedVertices>) {
-    v.clear();
+// the selection survives switching between select and move mode, but
+// is dropped on the way to anything else
+pub fn clear_selection_on_exit(
+    mut tr: MessageReader<StateTransitionEvent<AppState>>,
+    mut v: ResMut<SelectedVertices>,
+) {
+    for msg in tr.read() {
+        match msg.entered {
+            Some(AppState::Editing {
+                mode: EditorMode::Select | EditorMode::Move,
+            }) => (),
+            Some(AppState::Paused {
+                editing: Some(EditorMode::Select | EditorMode::Move),
+            }) => (),
+            _ => v.clear(),
+        }
+    }
 }
 
 pub fn collect_selected_vertices(
     puzzle: Single<&Puzzle>,
     mut sel: ResMut<SelectedVertices>,
     keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
 ) {
-    if keys.just_pressed(KeyCode::KeyC) {
+    if settings
+        .bindings
+        .just_pressed(Action::ClearSelection, &keys)
+    {
         sel.0.clear();
     }
 
-    if keys.pressed(KeyCode::ControlLeft) && keys.just_pressed(KeyCode::KeyA) {
+    if settings.bindings.just_pressed(Action::SelectAll, &keys) {
         for v in puzzle.vertices() {
             sel.insert(v.0);
         }
@@ -201,6 +217,120 @@ fn do_select(
     }
 }
 
+// vertices being dragged, and where the cursor was last frame
+struct DragState {
+    ids: HashSet<usize>,
+    last: Vec2,
+}
+
+fn do_move(
+    mut drag: Local<Option<DragState>>,
+    cursor: Res<CursorState>,
+    vinfo: Res<CursorVertexInfo>,
+    sel: Res<SelectedVertices>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut puzzle: Single<&mut Puzzle>,
+    save: Res<SaveData>,
+    mut history: ResMut<EditHistory>,
+    sprites: Query<(Entity, &Sprite, &Transform)>,
+    images: Res<Assets<Image>>,
+    settings: Res<Settings>,
+) {
+    if !buttons.pressed(MouseButton::Left) {
+        if drag.take().is_some() {
+            puzzle.update();
+            history.end_group(&puzzle, &save);
+        }
+        return;
+    }
+
+    let p = match cursor.get() {
+        Some(p) => p,
+        _ => return,
+    };
+
+    if buttons.just_pressed(MouseButton::Left) {
+        if let Some(id) = vinfo.hovered {
+            // dragging a selected vertex drags the whole selection
+            let ids = if sel.contains(&id) {
+                sel.0.clone()
+            } else {
+                HashSet::from([id])
+            };
+            history.begin_group("Move vertices", &puzzle, &save);
+            *drag = Some(DragState { ids, last: p });
+        }
+    }
+
+    let drag = match &mut *drag {
+        Some(d) => d,
+        None => return,
+    };
+
+    let delta = p - drag.last;
+    if delta == Vec2::ZERO {
+        return;
+    }
+    drag.last = p;
+
+    for id in &drag.ids {
+        puzzle.translate_vertex(*id, delta);
+    }
+
+    let keys = puzzle.faces_touching(&drag.ids);
+    sample_colors(&mut puzzle, &keys, &sprites, &images, settings.blend_scale);
+}
+
+fn nudge_vertices(
+    keys: Res<ButtonInput<KeyCode>>,
+    vinfo: Res<CursorVertexInfo>,
+    sel: Res<SelectedVertices>,
+    camera: Single<&Transform, With<Camera>>,
+    mut puzzle: Single<&mut Puzzle>,
+    mut save: ResMut<SaveData>,
+    mut history: ResMut<EditHistory>,
+    sprites: Query<(Entity, &Sprite, &Transform)>,
+    images: Res<Assets<Image>>,
+    settings: Res<Settings>,
+) {
+    let mut dir = Vec2::ZERO;
+    for (action, d) in [
+        (Action::NudgeLeft, Vec2::NEG_X),
+        (Action::NudgeRight, Vec2::X),
+        (Action::NudgeDown, Vec2::NEG_Y),
+        (Action::NudgeUp, Vec2::Y),
+    ] {
+        if settings.bindings.key_just_pressed(action, &keys) {
+            dir += d;
+        }
+    }
+
+    if dir == Vec2::ZERO {
+        return;
+    }
+
+    let ids = if !sel.is_empty() {
+        sel.0.clone()
+    } else if let Some(id) = vinfo.hovered {
+        HashSet::from([id])
+    } else {
+        return;
+    };
+
+    // one screen pixel per press, or ten with shift held
+    let shift = keys.pressed(KeyCode::ShiftLeft) || keys.pressed(KeyCode::ShiftRight);
+    let step = if shift { 10.0 } else { 1.0 } * camera.scale.x;
+
+    history.record("Nudge vertices", &mut puzzle, &mut save, |p, _| {
+        for id in &ids {
+            p.translate_vertex(*id, dir * step);
+        }
+        p.update();
+        let keys = p.faces_touching(&ids);
+        sample_colors(p, &keys, &sprites, &images, settings.blend_scale);
+    });
+}
+
 fn delete_edges_on_right_click(
     mouse: Res<ButtonInput<MouseButton>>,
     cursor: Res<CursorVertexInfo>,
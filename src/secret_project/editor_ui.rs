@@ -9,7 +9,8 @@ impl Plugin for EguiEditor {
         app.add_plugins(EguiPlugin::default())
             .add_message::<SavePuzzle>()
             .insert_resource(CurrentPuzzle(None))
-            .add_systems(Update, save_puzzle_system)
+            .init_resource::<AnalysisWorker>()
+            .add_systems(Update, (save_puzzle_system, poll_analysis))
             .add_systems(EguiPrimaryContextPass, editor_ui_system.run_if(is_editor));
     }
 }
@@ -18,7 +19,37 @@ impl Plugin for EguiEditor {
 pub struct SavePuzzle;
 
 #[derive(Resource)]
-pub struct CurrentPuzzle(pub Option<usize>);
+pub struct CurrentPuzzle(pub Option<PuzzleKey>);
+
+// the uniqueness check searches every possible edge, which is far too slow
+// to run on the main thread for a large puzzle
+#[derive(Resource, Default)]
+struct AnalysisWorker {
+    task: Option<Task<SolutionAnalysis>>,
+    result: Option<SolutionAnalysis>,
+}
+
+impl AnalysisWorker {
+    fn run(&mut self, puzzle: &Puzzle) {
+        let hints = puzzle.hints();
+        let solution = puzzle.solution_edges.clone();
+        let thread_pool = AsyncComputeTaskPool::get();
+        self.task = Some(thread_pool.spawn(async move { analyze_solutions(hints, &solution) }));
+        self.result = None;
+    }
+}
+
+fn poll_analysis(mut worker: ResMut<AnalysisWorker>) {
+    let Some(task) = &mut worker.task else {
+        return;
+    };
+    let Some(result) = future::block_on(future::poll_once(task)) else {
+        return;
+    };
+    info!("Solution analysis: {}", result);
+    worker.task = None;
+    worker.result = Some(result);
+}
 
 fn save_puzzle_system(
     mut commands: Commands,
@@ -26,7 +57,6 @@ fn save_puzzle_system(
     windows: Query<(&RefImagePath, &RefImageWindow)>,
     mut save: MessageReader<SavePuzzle>,
     current: Res<CurrentPuzzle>,
-    manifest: Res<Manifest>,
     install: Res<Installation>,
 ) {
     if save.is_empty() {
@@ -37,17 +67,12 @@ fn save_puzzle_system(
 
     let mut images = vec![];
 
-    let id = match current.0 {
-        Some(id) => id,
-        _ => return,
-    };
-
-    let info = match manifest.get(id) {
-        Some(info) => info,
+    let key = match &current.0 {
+        Some(key) => key,
         _ => return,
     };
 
-    info!("Saving puzzle: {:?}", info);
+    info!("Saving puzzle: {}", key);
 
     for (path, window) in windows {
         let path_str = format!("{}", path.0.file_name().unwrap().display());
@@ -58,7 +83,7 @@ fn save_puzzle_system(
         images.push(img);
     }
 
-    let path = install.puzzle_file(&info.short_name);
+    let path = install.pack(&key.pack).puzzle_file(&key.short_name);
 
     match puzzle_to_file(&puzzle, &path, images) {
         Ok(()) => {
@@ -80,20 +105,26 @@ fn save_puzzle_system(
 fn editor_ui_system(
     mut contexts: EguiContexts,
     mut commands: Commands,
-    mut app: ResMut<Settings>,
+    mut settings: ResMut<Settings>,
     mut puzzle: Single<&mut Puzzle>,
     mut save: ResMut<SaveData>,
     sprites: Query<(Entity, &Sprite, &Transform)>,
     images: Res<Assets<Image>>,
     keys: Res<ButtonInput<KeyCode>>,
     sel: Res<SelectedVertices>,
-    puzzle_list: Res<Manifest>,
+    packs: Res<PuzzlePacks>,
     mut mouse: ResMut<CursorState>,
     camera: Single<&Transform, With<Camera>>,
+    mut analysis: ResMut<AnalysisWorker>,
+    mut history: ResMut<EditHistory>,
 ) {
     mouse.on_egui = false;
 
-    if keys.pressed(KeyCode::ControlLeft) && keys.just_pressed(KeyCode::KeyS) {
+    // sliders are given a copy, so settings only count as changed (and get
+    // saved) when a value actually moves
+    let mut app = settings.clone();
+
+    if app.bindings.just_pressed(Action::SavePuzzle, &keys) {
         commands.write_message(SavePuzzle);
     }
 
@@ -115,26 +146,68 @@ fn editor_ui_system(
             ui.label(format!("{:#?}", *camera));
 
             ui.collapsing("Puzzles", |ui| {
-                for (id, info) in puzzle_list.puzzles.iter().enumerate() {
-                    if ui.button(&info.title).clicked() {
-                        info!("Opening a puzzle: {:?}", info);
-                        commands.write_message(OpenPuzzleById(id));
-                    }
+                for pack in &packs.0 {
+                    ui.collapsing(&pack.title, |ui| {
+                        for info in &pack.manifest.puzzles {
+                            if ui.button(&info.title).clicked() {
+                                info!("Opening a puzzle: {:?}", info);
+                                let key = PuzzleKey::new(&pack.name, &info.short_name);
+                                commands.write_message(OpenPuzzleByKey(key));
+                            }
+                        }
+                    });
                 }
             });
 
             ui.collapsing("Color Sampling", |ui| {
                 if ui.button("Sample Colors").clicked() {
-                    sample_colors(&mut puzzle, &save, &sprites, &images, app.blend_scale);
+                    let keys = puzzle.face_keys();
+                    history.record("Sample colors", &mut puzzle, &mut save, |p, _| {
+                        sample_colors(p, &keys, &sprites, &images, app.blend_scale)
+                    });
                 }
                 ui.add(egui::Slider::new(&mut app.blend_scale, 0.1..=0.9));
 
                 if ui.button("Quantize").clicked() {
-                    puzzle.quantize_colors(app.n_colors);
+                    history.record("Quantize", &mut puzzle, &mut save, |p, _| {
+                        p.quantize_colors(app.n_colors)
+                    });
                 }
                 ui.add(egui::Slider::new(&mut app.n_colors, 3..=500));
             });
 
+            ui.collapsing("Generator", |ui| {
+                ui.label("Vertices");
+                ui.add(egui::Slider::new(
+                    &mut app.generator_vertex_count,
+                    50..=5000,
+                ));
+                ui.label("Colors");
+                ui.add(egui::Slider::new(&mut app.n_colors, 3..=500));
+
+                if ui.button("Generate From Image").clicked() {
+                    let source = sprites
+                        .iter()
+                        .find_map(|(_, sprite, tf)| Some((images.get(sprite.image.id())?, tf)));
+
+                    if let Some((img, tf)) = source {
+                        history.record("Generate", &mut puzzle, &mut save, |p, s| {
+                            if let Err(e) =
+                                generate_from_image(p, s, img, tf, app.generator_vertex_count)
+                            {
+                                commands.write_message(TextMessage::info(format!("{:?}", e)));
+                                return;
+                            }
+                            let keys = p.face_keys();
+                            sample_colors(p, &keys, &sprites, &images, app.blend_scale);
+                            p.quantize_colors(app.n_colors);
+                        });
+                    } else {
+                        commands.write_message(TextMessage::info("Open a reference image first"));
+                    }
+                }
+            });
+
             ui.collapsing("Editor", |ui| {
                 if ui.button("Open Image").clicked() {
                     commands.write_message(FileMessage::OpenFile(FileType));
@@ -149,15 +222,17 @@ fn editor_ui_system(
                 }
 
                 if ui.button("Update").clicked() {
-                    puzzle.update();
+                    history.record("Update", &mut puzzle, &mut save, |p, _| p.update());
                 }
 
                 if ui.button("Triangulate").clicked() {
-                    puzzle.triangulate(sel);
+                    history.record("Triangulate", &mut puzzle, &mut save, |p, _| {
+                        p.triangulate(sel)
+                    });
                 }
 
-                if ui.button("Update Triangles").clicked() {
-                    puzzle.update();
+                if ui.button("Update Faces").clicked() {
+                    history.record("Update faces", &mut puzzle, &mut save, |p, _| p.update());
                 }
 
                 ui.separator();
@@ -168,6 +243,53 @@ fn editor_ui_system(
                 ui.add(egui::Slider::new(&mut app.triangle_alpha, 0.05..=1.0));
             });
 
+            ui.collapsing("History", |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Undo").clicked() {
+                        history.undo(&mut puzzle, &mut save);
+                    }
+                    if ui.button("Redo").clicked() {
+                        history.redo(&mut puzzle, &mut save);
+                    }
+                });
+
+                for label in history.redo_labels().collect::<Vec<_>>().into_iter().rev() {
+                    ui.weak(label);
+                }
+                for label in history.undo_labels().collect::<Vec<_>>().into_iter().rev() {
+                    ui.label(label);
+                }
+            });
+
+            ui.collapsing("Export", |ui| {
+                for (name, mode) in [
+                    ("Export Solution SVG", SvgMode::Solution),
+                    ("Export Blank SVG", SvgMode::Blank),
+                    ("Export Progress SVG", SvgMode::Progress),
+                ] {
+                    if ui.button(name).clicked() {
+                        commands.write_message(ExportSvg(mode));
+                    }
+                }
+            });
+
+            ui.collapsing("Analysis", |ui| {
+                if analysis.task.is_some() {
+                    ui.label("Checking...");
+                } else if ui.button("Check Uniqueness").clicked() {
+                    analysis.run(&puzzle);
+                }
+
+                if let Some(result) = &analysis.result {
+                    ui.label(format!("{}", result));
+                    if let SolutionAnalysis::Multiple(alternate) = result {
+                        if ui.button("Load Alternate Into Save").clicked() {
+                            save.edges = alternate.clone();
+                        }
+                    }
+                }
+            });
+
             ui.collapsing("Alerts", |ui| {
                 if ui.button("Send Text Alert").clicked() {
                     commands.write_message(TextMessage::info("This is a text alert!"));
@@ -184,25 +306,31 @@ fn editor_ui_system(
         });
 
     mouse.on_egui = ctx.is_pointer_over_area();
+
+    if app != *settings {
+        *settings = app;
+    }
 }
 
-fn sample_colors(
+pub fn sample_colors(
     puzzle: &mut Puzzle,
-    save: &SaveData,
+    keys: &[FaceKey],
     sprites: &Query<(Entity, &Sprite, &Transform)>,
-    images: &Res<Assets<Image>>,
+    images: &Assets<Image>,
     blend_scale: f32,
 ) {
-    let triangles: Vec<_> = puzzle
-        .triangles(&save, false)
-        .map(|(a, b, c, _)| (a, b, c))
-        .collect();
-
-    for (a, b, c) in triangles {
-        let center = (a + b + c) / 3.0;
-        let a = center.lerp(a, blend_scale);
-        let b = center.lerp(b, blend_scale);
-        let c = center.lerp(c, blend_scale);
+    for key in keys {
+        let points = match puzzle.face_points(key) {
+            Some(points) => points,
+            None => continue,
+        };
+
+        let center = points.iter().sum::<Vec2>() / points.len() as f32;
+        let mut samples: Vec<Vec2> = points
+            .iter()
+            .map(|p| center.lerp(*p, blend_scale))
+            .collect();
+        samples.push(center);
         for (_, sprite, tf) in sprites {
             let img = if let Some(img) = images.get(sprite.image.id()) {
                 img
@@ -228,26 +356,21 @@ fn sample_colors(
                 }
             };
 
-            let ca = sample_color(a);
-            let cb = sample_color(b);
-            let cc = sample_color(c);
-            let cd = sample_color(center);
-
-            let iter = ca.iter().chain(cb.iter()).chain(cc.iter()).chain(cd.iter());
-            let n = iter.clone().count();
+            let colors: Vec<Srgba> = samples.iter().filter_map(|q| sample_color(*q)).collect();
+            let n = colors.len();
             if n == 0 {
                 continue;
             }
 
             let mut blended = Srgba::BLACK;
 
-            for color in iter {
+            for color in colors {
                 blended.red += color.red / n as f32;
                 blended.green += color.green / n as f32;
                 blended.blue += color.blue / n as f32;
             }
 
-            puzzle.set_triangle_color(center, blended);
+            puzzle.set_face(key.clone(), Some(blended));
         }
     }
 }
@@ -0,0 +1,81 @@
+use crate::secret_project::*;
+
+// what a server said about a url, so that the next request can ask whether
+// it has changed instead of downloading it again
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+// the last response for each url, kept in the installation. each url gets
+// its own pair of files, so concurrent downloads never share one
+#[derive(Debug, Clone)]
+pub struct FetchCache {
+    dir: PathBuf,
+}
+
+impl FetchCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn body_file(&self, url: &str) -> PathBuf {
+        self.dir.join(sha256_hex(url.as_bytes()))
+    }
+
+    fn validators_file(&self, url: &str) -> PathBuf {
+        self.body_file(url).with_extension("yaml")
+    }
+
+    // only offered if the body is still there to fall back on
+    pub fn validators(&self, url: &str) -> Option<CacheValidators> {
+        if !std::fs::exists(self.body_file(url)).ok()? {
+            return None;
+        }
+        load_from_file(&self.validators_file(url)).ok()
+    }
+
+    pub fn store(
+        &self,
+        url: &str,
+        body: &Path,
+        validators: &CacheValidators,
+    ) -> Result<(), VertexError> {
+        if validators.is_empty() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        // until the new validators are written, the old ones would describe
+        // the wrong body
+        self.remove(url)?;
+        let part = self.body_file(url).with_extension("part");
+        std::fs::copy(body, &part)?;
+        std::fs::rename(&part, self.body_file(url))?;
+        save_to_file(validators, &self.validators_file(url))?;
+        Ok(())
+    }
+
+    // forgets a url, so the next request downloads it in full
+    pub fn remove(&self, url: &str) -> Result<(), VertexError> {
+        for path in [self.validators_file(url), self.body_file(url)] {
+            match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    // copies the cached body for a url to dest, as if it had just been
+    // downloaded
+    pub fn restore(&self, url: &str, dest: &Path) -> Result<u64, VertexError> {
+        Ok(std::fs::copy(self.body_file(url), dest)?)
+    }
+}
@@ -8,7 +8,7 @@ pub struct FilePlugin;
 impl Plugin for FilePlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<FileMessage>();
-        app.add_message::<OpenPuzzleById>();
+        app.add_message::<OpenPuzzleByKey>();
         app.add_systems(Update, (open_dialogue, poll_tasks));
     }
 }
@@ -34,7 +34,7 @@ struct SelectedFile {
 }
 
 #[derive(Message, Debug)]
-pub struct OpenPuzzleById(pub usize);
+pub struct OpenPuzzleByKey(pub PuzzleKey);
 
 fn open_dialogue(mut commands: Commands, mut msg: MessageReader<FileMessage>) {
     for msg in msg.read() {
@@ -1,32 +1,125 @@
 use crate::secret_project::*;
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PuzzleManifestInfo {
     pub short_name: String,
     pub title: String,
+    // bumped by the publisher whenever the puzzle file changes
+    #[serde(default)]
+    pub version: u32,
+    // sha-256 of the puzzle file, as lowercase hex
+    #[serde(default)]
+    pub hash: Option<String>,
+    // largest the puzzle file may be, in bytes; it can only lower the
+    // limit, which is MAX_PUZZLE_SIZE if unset
+    #[serde(default)]
+    pub max_size: Option<u64>,
+
+    // everything below is optional, for showing and searching puzzles
+    #[serde(default)]
+    pub author: Option<String>,
+    // from 1 (easiest) to MAX_DIFFICULTY
+    #[serde(default)]
+    pub difficulty: Option<u8>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    // as written by the publisher, eg. 2025-06-01
+    #[serde(default)]
+    pub published: Option<String>,
+    #[serde(default)]
+    pub vertex_count: Option<usize>,
+    #[serde(default)]
+    pub triangle_count: Option<usize>,
+    // hex colors the puzzle looks best with, eg. "#e0a030"
+    #[serde(default)]
+    pub palette: Vec<String>,
 }
 
+pub const MAX_DIFFICULTY: u8 = 5;
+
 impl PuzzleManifestInfo {
     pub fn new(short_name: impl Into<String>, title: impl Into<String>) -> Self {
         Self {
             short_name: short_name.into(),
             title: title.into(),
+            ..default()
         }
     }
+
+    pub fn size_limit(&self) -> u64 {
+        self.max_size
+            .map_or(MAX_PUZZLE_SIZE, |m| m.min(MAX_PUZZLE_SIZE))
+    }
+
+    pub fn difficulty_label(&self) -> Option<String> {
+        let d = self.difficulty?.clamp(1, MAX_DIFFICULTY);
+        Some(format!("Difficulty {}/{}", d, MAX_DIFFICULTY))
+    }
+
+    // one line of whatever metadata the manifest has, for the puzzle list
+    pub fn details(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            self.author.as_ref().map(|a| format!("by {}", a)),
+            self.difficulty_label(),
+            self.vertex_count.map(|n| format!("{} vertices", n)),
+            self.triangle_count.map(|n| format!("{} triangles", n)),
+            (!self.tags.is_empty()).then(|| self.tags.join(", ")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        (!parts.is_empty()).then(|| parts.join("  |  "))
+    }
+
+    pub fn palette_colors(&self) -> Vec<Srgba> {
+        self.palette
+            .iter()
+            .filter_map(|hex| Srgba::hex(hex).ok())
+            .collect()
+    }
 }
 
-#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Manifest {
     pub puzzles: Vec<PuzzleManifestInfo>,
+    // only read from the source's own manifest; packs can't list packs
+    #[serde(default)]
+    pub packs: Vec<PackInfo>,
 }
 
 impl Manifest {
+    // an installed manifest is only ever written by a sync, from manifests
+    // which passed from_signed_file, so it's loaded as is. it may not match
+    // the signature beside it: a sync which partly fails keeps old entries
+    // for the failed puzzles, and can't sign the result. anyone who can
+    // edit the installation can edit the settings, key included, anyway
     pub fn from_file(path: &Path) -> Result<Self, VertexError> {
         info!("Loading manifest at {}", path.display());
         Ok(load_from_file(path)?)
     }
 
+    // refuses a manifest without a valid signature next to it, unless the
+    // verifier trusts its source
+    pub fn from_signed_file(path: &Path, verifier: &ManifestVerifier) -> Result<Self, VertexError> {
+        info!("Verifying manifest at {}", path.display());
+        let contents = std::fs::read(path)?;
+        let signature = match std::fs::read_to_string(signature_file(path)) {
+            Ok(s) => Some(s),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        verifier.verify(&path.display().to_string(), &contents, signature.as_deref())?;
+        Ok(serde_yaml::from_slice(&contents)?)
+    }
+
     pub fn get(&self, idx: usize) -> Option<&PuzzleManifestInfo> {
         self.puzzles.get(idx)
     }
+
+    pub fn find(&self, short_name: &str) -> Option<&PuzzleManifestInfo> {
+        self.puzzles.iter().find(|p| p.short_name == short_name)
+    }
 }
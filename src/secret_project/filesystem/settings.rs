@@ -1,11 +1,27 @@
 use crate::secret_project::*;
 
-#[derive(Resource, Deserialize, Serialize)]
+// fields missing from an older settings file take their default values
+#[derive(Resource, Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
 pub struct Settings {
     pub ref_image_alpha: f32,
     pub triangle_alpha: f32,
     pub blend_scale: f32,
     pub n_colors: u16,
+    pub generator_vertex_count: usize,
+    pub volume: f32,
+    pub vertex_size: f32,
+    pub show_hints: bool,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub bindings: KeyBindings,
+    // a base url, or a local directory laid out the same way
+    pub puzzle_source: String,
+    // hex ed25519 key manifests must be signed with, for self-hosted
+    // sources; the built in key if unset
+    pub manifest_key: Option<String>,
+    // sources whose manifests are installed without checking signatures
+    pub trusted_sources: Vec<String>,
 }
 
 impl Default for Settings {
@@ -15,6 +31,22 @@ impl Default for Settings {
             triangle_alpha: 1.0,
             blend_scale: 0.5,
             n_colors: 16,
+            generator_vertex_count: 400,
+            volume: 1.0,
+            vertex_size: 1.0,
+            show_hints: true,
+            fullscreen: false,
+            vsync: true,
+            bindings: KeyBindings::default(),
+            puzzle_source: DEFAULT_PUZZLE_SOURCE.to_string(),
+            manifest_key: None,
+            trusted_sources: Vec::new(),
         }
     }
 }
+
+impl Settings {
+    pub fn from_file(path: &Path) -> Result<Self, VertexError> {
+        load_from_file(path)
+    }
+}
@@ -0,0 +1,249 @@
+use crate::secret_project::*;
+use rand::Rng;
+
+// images are analyzed at no more than this many pixels across
+const MAX_ANALYSIS_WIDTH: u32 = 400;
+
+// how much of a point's chance of being placed comes from flat regions
+// rather than from detail; keeps large smooth areas from going empty
+const BASE_WEIGHT: f32 = 0.05;
+
+// per-pixel sampling weights for an image, row-major from the top left
+pub struct DetailMap {
+    pub width: usize,
+    pub height: usize,
+    pub weights: Vec<f32>,
+}
+
+impl DetailMap {
+    pub fn from_luma(width: usize, height: usize, luma: &[f32]) -> Self {
+        let at = |x: usize, y: usize| luma[y.min(height - 1) * width + x.min(width - 1)];
+
+        let mut weights = vec![0.0; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let gx = at(x + 1, y) - at(x.saturating_sub(1), y);
+                let gy = at(x, y + 1) - at(x, y.saturating_sub(1));
+                weights[y * width + x] = Vec2::new(gx, gy).length();
+            }
+        }
+
+        let max = weights.iter().copied().fold(0.0, f32::max);
+        for w in &mut weights {
+            *w = BASE_WEIGHT + (1.0 - BASE_WEIGHT) * if max > 0.0 { *w / max } else { 0.0 };
+        }
+
+        Self {
+            width,
+            height,
+            weights,
+        }
+    }
+
+    fn from_image(img: &Image) -> Option<Self> {
+        let size = img.size();
+        if size.x == 0 || size.y == 0 {
+            return None;
+        }
+
+        let step = size.x.div_ceil(MAX_ANALYSIS_WIDTH);
+        let width = size.x.div_ceil(step) as usize;
+        let height = size.y.div_ceil(step) as usize;
+
+        let mut luma = Vec::with_capacity(width * height);
+        for y in 0..height as u32 {
+            for x in 0..width as u32 {
+                let c = img.get_color_at(x * step, y * step).ok()?.to_srgba();
+                luma.push(0.299 * c.red + 0.587 * c.green + 0.114 * c.blue);
+            }
+        }
+
+        Some(Self::from_luma(width, height, &luma))
+    }
+}
+
+// picks points in map coordinates, denser where the map has more detail.
+// the border of the map is always outlined so the puzzle covers the image.
+pub fn place_points(map: &DetailMap, count: usize, rng: &mut impl Rng) -> Vec<Vec2> {
+    let w = map.width as f32;
+    let h = map.height as f32;
+    let spacing = (w * h / count.max(1) as f32).sqrt();
+
+    let mut points = Vec::new();
+
+    // the border, evenly spaced
+    let nx = (w / spacing).ceil().max(1.0) as usize;
+    let ny = (h / spacing).ceil().max(1.0) as usize;
+    for i in 0..nx {
+        let x = w * i as f32 / nx as f32;
+        points.push(Vec2::new(x, 0.0));
+        points.push(Vec2::new(w - x, h));
+    }
+    for i in 0..ny {
+        let y = h * i as f32 / ny as f32;
+        points.push(Vec2::new(w, y));
+        points.push(Vec2::new(0.0, h - y));
+    }
+
+    let mut cumulative = Vec::with_capacity(map.weights.len());
+    let mut total = 0.0;
+    for w in &map.weights {
+        total += w;
+        cumulative.push(total);
+    }
+
+    if total <= 0.0 {
+        return points;
+    }
+
+    // points closer than this are rejected; detailed regions get points
+    // up to three times closer together than flat ones
+    let min_distance = spacing / 3.0;
+    let cell = min_distance.max(1.0);
+    let mut occupied: HashMap<(i32, i32), Vec<Vec2>> = HashMap::new();
+    let key = |p: Vec2| ((p.x / cell) as i32, (p.y / cell) as i32);
+
+    for p in &points {
+        occupied.entry(key(*p)).or_default().push(*p);
+    }
+
+    let mut attempts = 0;
+    while points.len() < count && attempts < count * 30 {
+        attempts += 1;
+
+        let target = rng.random_range(0.0..total);
+        let i = cumulative
+            .partition_point(|c| *c < target)
+            .min(cumulative.len() - 1);
+        let x = (i % map.width) as f32 + rng.random_range(0.0..1.0);
+        let y = (i / map.width) as f32 + rng.random_range(0.0..1.0);
+        let p = Vec2::new(x, y);
+
+        // flat regions want more room around each point than detailed ones
+        let r = min_distance / map.weights[i].sqrt();
+
+        let (cx, cy) = key(p);
+        let reach = (r / cell).ceil() as i32;
+        let crowded = (-reach..=reach).any(|dx| {
+            (-reach..=reach).any(|dy| {
+                occupied
+                    .get(&(cx + dx, cy + dy))
+                    .is_some_and(|list| list.iter().any(|q| q.distance(p) < r))
+            })
+        });
+
+        if crowded {
+            continue;
+        }
+
+        occupied.entry((cx, cy)).or_default().push(p);
+        points.push(p);
+    }
+
+    points
+}
+
+fn delaunay_edges(points: &[Vec2]) -> Vec<(usize, usize)> {
+    let points: Vec<_> = points
+        .iter()
+        .map(|p| delaunator::Point {
+            x: p.x as f64,
+            y: p.y as f64,
+        })
+        .collect();
+
+    let tri = delaunator::triangulate(&points);
+
+    tri.triangles
+        .chunks_exact(3)
+        .flat_map(|t| [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])])
+        .collect()
+}
+
+// replaces the puzzle with one generated from a reference image. vertex
+// ids start again from zero, so the player's edges no longer mean anything
+pub fn generate_from_image(
+    puzzle: &mut Puzzle,
+    save: &mut SaveData,
+    img: &Image,
+    tf: &Transform,
+    vertex_count: usize,
+) -> Result<(), VertexError> {
+    let map = DetailMap::from_image(img).ok_or("Reference image isn't readable")?;
+
+    // map coordinates run from the top left; the world is y-up and the
+    // sprite is centered on its transform
+    let size = Vec2::new(map.width as f32, map.height as f32);
+    let world_size = img.size().as_vec2() * tf.scale.xy();
+    let to_world = |p: Vec2| {
+        let uv = Vec2::new(p.x, size.y - p.y) / size;
+        tf.translation.xy() - world_size / 2.0 + uv * world_size
+    };
+
+    let points: Vec<Vec2> = place_points(&map, vertex_count, &mut rand::rng())
+        .into_iter()
+        .map(to_world)
+        .collect();
+
+    *puzzle = Puzzle::default();
+    save.edges = Edges::default();
+    let ids: Vec<usize> = points.iter().map(|p| puzzle.add_point(*p)).collect();
+
+    for (a, b) in delaunay_edges(&points) {
+        puzzle.solution_edges.add_edge(ids[a], ids[b]);
+    }
+
+    puzzle.update();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn striped(width: usize, height: usize) -> DetailMap {
+        // dark on the left half, light on the right
+        let luma: Vec<f32> = (0..width * height)
+            .map(|i| if i % width < width / 2 { 0.0 } else { 1.0 })
+            .collect();
+        DetailMap::from_luma(width, height, &luma)
+    }
+
+    #[test]
+    fn points_stay_inside_the_map() {
+        let map = striped(100, 60);
+        let points = place_points(&map, 200, &mut StdRng::seed_from_u64(1));
+        assert!(points.len() > 50);
+        for p in points {
+            assert!(p.x >= 0.0 && p.x <= 100.0);
+            assert!(p.y >= 0.0 && p.y <= 60.0);
+        }
+    }
+
+    #[test]
+    fn detail_attracts_points() {
+        let map = striped(100, 100);
+        let points = place_points(&map, 300, &mut StdRng::seed_from_u64(2));
+        let near_edge = points.iter().filter(|p| (p.x - 50.0).abs() < 10.0).count();
+        let far_from_edge = points.iter().filter(|p| (p.x - 20.0).abs() < 10.0).count();
+        assert!(near_edge > far_from_edge);
+    }
+
+    #[test]
+    fn generating_clears_the_players_edges() {
+        use image::{Rgba, RgbaImage};
+
+        let pixels = RgbaImage::from_pixel(40, 40, Rgba([200, 40, 40, 255]));
+        let img = Image::from_dynamic(pixels.into(), true, RenderAssetUsages::default());
+        let mut puzzle = Puzzle::default();
+        let mut save = SaveData::default();
+        save.edges.add_edge(0, 1);
+
+        generate_from_image(&mut puzzle, &mut save, &img, &Transform::default(), 50).unwrap();
+        assert!(save.edges.0.is_empty());
+        assert!(puzzle.vertices().count() > 0);
+    }
+}
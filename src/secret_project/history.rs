@@ -0,0 +1,198 @@
+use crate::secret_project::*;
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EditHistory::default()).add_systems(
+            Update,
+            (
+                undo_redo_on_keys.run_if(is_editor).before(PuzzleEdits),
+                begin_eraser_stroke
+                    .run_if(in_state(AppState::Editing {
+                        mode: EditorMode::Eraser,
+                    }))
+                    .before(PuzzleEdits),
+                end_eraser_stroke.after(PuzzleEdits),
+            ),
+        );
+    }
+}
+
+const MAX_HISTORY: usize = 200;
+
+pub struct EditCommand {
+    pub label: String,
+    diff: PuzzleDiff,
+}
+
+#[derive(Resource, Default)]
+pub struct EditHistory {
+    undo: Vec<EditCommand>,
+    redo: Vec<EditCommand>,
+    group: Option<(String, PuzzleSnapshot)>,
+}
+
+impl EditHistory {
+    // runs an edit on the puzzle and records whatever it changed
+    pub fn record(
+        &mut self,
+        label: impl Into<String>,
+        puzzle: &mut Puzzle,
+        save: &mut SaveData,
+        edit: impl FnOnce(&mut Puzzle, &mut SaveData),
+    ) {
+        if self.group.is_some() {
+            edit(puzzle, save);
+            return;
+        }
+
+        let before = puzzle.snapshot(save);
+        edit(puzzle, save);
+        self.push(label, &before, puzzle, save);
+    }
+
+    fn push(
+        &mut self,
+        label: impl Into<String>,
+        before: &PuzzleSnapshot,
+        puzzle: &Puzzle,
+        save: &SaveData,
+    ) {
+        let diff = PuzzleDiff::between(before, &puzzle.snapshot(save));
+        if diff.is_empty() {
+            return;
+        }
+
+        self.undo.push(EditCommand {
+            label: label.into(),
+            diff,
+        });
+        self.redo.clear();
+
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+    }
+
+    // until the group ends, every recorded edit is folded into one command
+    pub fn begin_group(&mut self, label: impl Into<String>, puzzle: &Puzzle, save: &SaveData) {
+        if self.group.is_none() {
+            self.group = Some((label.into(), puzzle.snapshot(save)));
+        }
+    }
+
+    pub fn end_group(&mut self, puzzle: &Puzzle, save: &SaveData) {
+        if let Some((label, before)) = self.group.take() {
+            self.push(label, &before, puzzle, save);
+        }
+    }
+
+    pub fn undo(&mut self, puzzle: &mut Puzzle, save: &mut SaveData) -> Option<&str> {
+        self.end_group(puzzle, save);
+        let cmd = self.undo.pop()?;
+        cmd.diff.apply(puzzle, save, false);
+        self.redo.push(cmd);
+        self.redo.last().map(|c| c.label.as_str())
+    }
+
+    pub fn redo(&mut self, puzzle: &mut Puzzle, save: &mut SaveData) -> Option<&str> {
+        self.end_group(puzzle, save);
+        let cmd = self.redo.pop()?;
+        cmd.diff.apply(puzzle, save, true);
+        self.undo.push(cmd);
+        self.undo.last().map(|c| c.label.as_str())
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group = None;
+    }
+
+    // oldest first
+    pub fn undo_labels(&self) -> impl Iterator<Item = &str> + use<'_> {
+        self.undo.iter().map(|c| c.label.as_str())
+    }
+
+    // next to be redone first
+    pub fn redo_labels(&self) -> impl Iterator<Item = &str> + use<'_> {
+        self.redo.iter().rev().map(|c| c.label.as_str())
+    }
+}
+
+fn undo_redo_on_keys(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut history: ResMut<EditHistory>,
+    mut puzzle: Single<&mut Puzzle>,
+    mut save: ResMut<SaveData>,
+    app: Res<Settings>,
+) {
+    let label = if app.bindings.just_pressed(Action::Redo, &keys) {
+        history
+            .redo(&mut puzzle, &mut save)
+            .map(|s| format!("Redo: {}", s))
+    } else if app.bindings.just_pressed(Action::Undo, &keys) {
+        history
+            .undo(&mut puzzle, &mut save)
+            .map(|s| format!("Undo: {}", s))
+    } else {
+        return;
+    };
+
+    if let Some(label) = label {
+        commands.write_message(TextMessage::debug(label));
+    }
+}
+
+fn begin_eraser_stroke(
+    mouse: Res<ButtonInput<MouseButton>>,
+    cursor: Res<CursorState>,
+    mut history: ResMut<EditHistory>,
+    puzzle: Single<&Puzzle>,
+    save: Res<SaveData>,
+) {
+    if mouse.just_pressed(MouseButton::Left) && cursor.get().is_some() {
+        history.begin_group("Eraser stroke", &puzzle, &save);
+    }
+}
+
+fn end_eraser_stroke(
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut history: ResMut<EditHistory>,
+    puzzle: Single<&Puzzle>,
+    save: Res<SaveData>,
+) {
+    if !mouse.pressed(MouseButton::Left) {
+        history.end_group(&puzzle, &save);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_restores_the_players_edges() {
+        let mut puzzle = Puzzle::default();
+        let mut save = SaveData::default();
+        let a = puzzle.add_point(Vec2::ZERO);
+        let b = puzzle.add_point(Vec2::new(10.0, 0.0));
+        puzzle.add_solution_edge(a, b);
+        save.edges.add_edge(a, b);
+
+        let mut history = EditHistory::default();
+        history.record("Delete vertex", &mut puzzle, &mut save, |p, save| {
+            p.remove_vertex(a, save)
+        });
+        assert!(!save.edges.is_edge(a, b));
+
+        history.undo(&mut puzzle, &mut save);
+        assert!(save.edges.is_edge(a, b));
+        assert!(puzzle.solution_edges.is_edge(a, b));
+
+        history.redo(&mut puzzle, &mut save);
+        assert!(!save.edges.is_edge(a, b));
+    }
+}
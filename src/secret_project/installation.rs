@@ -1,4 +1,5 @@
 use crate::secret_project::*;
+use std::sync::atomic::AtomicU64;
 
 #[derive(Resource, Debug, Clone)]
 pub struct Installation(PathBuf);
@@ -8,6 +9,12 @@ impl Installation {
         Self(root.into())
     }
 
+    // where the game keeps its files unless told otherwise
+    pub fn default_root() -> PathBuf {
+        let home_dir = std::env::home_dir().expect("Expected home directory");
+        home_dir.join(".vertex_install")
+    }
+
     pub fn initialize(root: impl Into<PathBuf>) -> Result<Self, VertexError> {
         let install = Self::new(root);
         initialize_install_directory(&install)?;
@@ -42,6 +49,10 @@ impl Installation {
         self.puzzle_dir(short_name).join("puzzle.yaml")
     }
 
+    pub fn thumbnail_file(&self, short_name: &str) -> PathBuf {
+        self.puzzle_dir(short_name).join("thumbnail.png")
+    }
+
     pub fn save_data_file(&self, short_name: &str) -> PathBuf {
         self.save_data().join(short_name).with_extension("yaml")
     }
@@ -49,11 +60,31 @@ impl Installation {
     pub fn settings(&self) -> PathBuf {
         self.0.join("settings.yaml")
     }
+
+    pub fn exports(&self) -> PathBuf {
+        self.0.join("exports")
+    }
+
+    // responses kept for conditional requests
+    pub fn fetch_cache(&self) -> PathBuf {
+        self.0.join("cache")
+    }
+
+    // the same layout, for one pack's manifest, puzzles and save data. the
+    // default pack lives in the root
+    pub fn pack(&self, name: &str) -> Installation {
+        match name {
+            DEFAULT_PACK => self.clone(),
+            _ => Self(self.0.join("packs").join(name)),
+        }
+    }
 }
 
 fn create_settings_file(install: &Installation) -> Result<(), VertexError> {
     let path = install.settings();
-    save_to_file(&Settings::default(), &path)?;
+    if !std::fs::exists(&path)? {
+        save_to_file(&Settings::default(), &path)?;
+    }
     Ok(())
 }
 
@@ -92,24 +123,44 @@ pub fn initialize_install_directory(install: &Installation) -> Result<(), Vertex
 
 pub fn install_remote_manifest(
     install: &Installation,
+    source: &dyn PuzzleSource,
+    verifier: &ManifestVerifier,
     overwrite: bool,
-) -> Result<u64, VertexError> {
+) -> Result<(), VertexError> {
     let path = install.network_manifest();
-    download_file(NETWORK_MANIFEST_URL, &path, overwrite)
+    if std::fs::exists(&path)? && !overwrite {
+        info!("{} already exists", path.display());
+        return Ok(());
+    }
+    fetch_manifest(source, &path, verifier)?;
+    commit_manifest(&path)
 }
 
 pub fn install_puzzle_file(
     install: &Installation,
+    source: &dyn PuzzleSource,
     short_name: &str,
     overwrite: bool,
 ) -> Result<u64, VertexError> {
     let puzzle_path = install.puzzle_file(short_name);
-    let url = puzzle_file_url(short_name);
     let dirname = install.puzzle_dir(short_name);
     if !std::fs::exists(&dirname)? {
         std::fs::create_dir(dirname)?;
     }
-    download_file(&url, &puzzle_path, overwrite)
+    if std::fs::exists(&puzzle_path)? && !overwrite {
+        info!("{} already exists", puzzle_path.display());
+        return Ok(0);
+    }
+    let file = puzzle_file_path(short_name);
+    fetch_verified(
+        source,
+        &file,
+        &puzzle_path,
+        None,
+        MAX_PUZZLE_SIZE,
+        &AtomicU64::default(),
+        |_| Ok(()),
+    )
 }
 
 #[cfg(test)]
@@ -127,10 +178,19 @@ mod tests {
         let install = Installation::initialize(test_dir).unwrap();
         let short_name = "rose";
 
-        assert!(install_puzzle_file(&install, short_name, false).is_ok());
+        let mut source = MemorySource::new();
+        source.insert(puzzle_file_path(short_name), "vertices: {}");
+        source.insert(MANIFEST_PATH, "puzzles: []");
+
+        assert!(install_puzzle_file(&install, &source, short_name, false).is_ok());
         assert!(std::fs::exists(install.puzzle_file(short_name)).unwrap());
 
-        assert!(install_remote_manifest(&install, false).is_ok());
+        assert!(
+            install_remote_manifest(&install, &source, &ManifestVerifier::trusted(), false).is_ok()
+        );
+        assert!(
+            install_remote_manifest(&install, &source, &ManifestVerifier::new(""), true).is_err()
+        );
         assert!(std::fs::exists(install.network_manifest()).unwrap());
     }
 }
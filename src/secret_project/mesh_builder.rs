@@ -55,6 +55,27 @@ impl MeshMaker {
         ]);
     }
 
+    // any simple polygon, convex or not, in either winding
+    pub fn polygon(&mut self, points: &[Vec2]) {
+        if points.len() < 3 {
+            return;
+        }
+
+        let n = self.positions.len() as u32;
+
+        for p in points {
+            self.positions.push(to_arr(*p));
+            self.normals.push([0.0, 0.0, 1.0]);
+            self.uvs.push([0.0, 0.0]);
+            self.colors.push(self.color.to_f32_array());
+        }
+
+        for [a, b, c] in triangulate_polygon(points) {
+            self.indices
+                .extend_from_slice(&[n + a as u32, n + b as u32, n + c as u32]);
+        }
+    }
+
     pub fn rectangle(&mut self, points: [Vec2; 4]) {
         let n = self.positions.len() as u32;
 
@@ -114,3 +135,93 @@ impl MeshMaker {
         ]);
     }
 }
+
+// splits a simple polygon into triangles by clipping ears: a corner which
+// turns the same way as the polygon and has no other point inside it can be
+// cut off without the triangle leaving the polygon
+pub fn triangulate_polygon(points: &[Vec2]) -> Vec<[usize; 3]> {
+    let n = points.len();
+    if n < 3 {
+        return Vec::new();
+    }
+
+    let area: f32 = (0..n)
+        .map(|i| points[i].perp_dot(points[(i + 1) % n]))
+        .sum();
+    let mut remaining: Vec<usize> = match area < 0.0 {
+        true => (0..n).rev().collect(),
+        false => (0..n).collect(),
+    };
+
+    let mut triangles = Vec::new();
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let corner = |k: usize| {
+            let a = remaining[(k + m - 1) % m];
+            let b = remaining[k];
+            let c = remaining[(k + 1) % m];
+            (a, b, c)
+        };
+        let is_ear = |k: usize| {
+            let (a, b, c) = corner(k);
+            let (pa, pb, pc) = (points[a], points[b], points[c]);
+            (pb - pa).perp_dot(pc - pa) > 0.0
+                && remaining.iter().all(|i| {
+                    let p = points[*i];
+                    p == pa || p == pb || p == pc || !point_in_triangle(p, pa, pb, pc)
+                })
+        };
+
+        // a polygon which isn't simple may have no ears; a fan is the best
+        // that can be done with what's left
+        let Some(k) = (0..m).find(|k| is_ear(*k)) else {
+            for k in 1..m - 1 {
+                triangles.push([remaining[0], remaining[k], remaining[k + 1]]);
+            }
+            return triangles;
+        };
+
+        let (a, b, c) = corner(k);
+        triangles.push([a, b, c]);
+        remaining.remove(k);
+    }
+
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(a: Vec2, b: Vec2, c: Vec2) -> f32 {
+        (b - a).perp_dot(c - a).abs() / 2.0
+    }
+
+    #[test]
+    fn concave_polygons_stay_inside() {
+        // an L, starting from the inside corner's neighbor so that a fan
+        // from the first point would cut across the notch
+        let points = [
+            Vec2::new(2.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 2.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+        ];
+
+        for winding in [points.to_vec(), points.iter().rev().copied().collect()] {
+            let triangles = triangulate_polygon(&winding);
+            assert_eq!(triangles.len(), winding.len() - 2);
+
+            let mut total = 0.0;
+            for [a, b, c] in triangles {
+                let (a, b, c) = (winding[a], winding[b], winding[c]);
+                total += area(a, b, c);
+                assert!(point_in_polygon((a + b + c) / 3.0, &winding));
+            }
+            assert!((total - 3.0).abs() < 1e-4);
+        }
+    }
+}
@@ -1,27 +1,40 @@
 mod autosolver;
+mod bindings;
 mod camera;
 mod confetti;
 mod constants;
 mod cursor;
 mod drawing;
 mod editor_ui;
+mod fetch_cache;
 mod file_open_system;
 mod filesystem;
+mod generator;
 mod grid;
 mod hidden_text;
+mod history;
 mod installation;
 mod math;
 mod mesh_builder;
 mod network;
+mod options;
+mod packs;
 mod particles;
 mod puzzle;
 mod reference_image;
+mod search;
+mod signing;
 mod sounds;
+mod source;
 mod states;
+mod svg;
+mod sync;
 mod text_alerts;
+mod thumbnail;
 mod ui;
 
 pub use autosolver::*;
+pub use bindings::*;
 pub use camera::*;
 pub use confetti::*;
 pub use constants::*;
@@ -29,20 +42,31 @@ pub use cursor::*;
 pub use drawing::*;
 pub use editor_ui::EguiEditor;
 pub use editor_ui::*;
+pub use fetch_cache::*;
 pub use file_open_system::*;
 pub use filesystem::*;
+pub use generator::*;
 pub use grid::*;
 pub use hidden_text::*;
+pub use history::*;
 pub use installation::*;
 pub use math::*;
 pub use mesh_builder::*;
 pub use network::*;
+pub use options::*;
+pub use packs::*;
 pub use particles::*;
 pub use puzzle::*;
 pub use reference_image::*;
+pub use search::*;
+pub use signing::*;
 pub use sounds::*;
+pub use source::*;
 pub use states::*;
+pub use svg::*;
+pub use sync::*;
 pub use text_alerts::*;
+pub use thumbnail::*;
 pub use ui::*;
 
 pub use bevy::asset::RenderAssetUsages;
@@ -61,4 +85,5 @@ pub use futures_lite::future;
 pub use serde::{Deserialize, Serialize};
 pub use std::collections::*;
 pub use std::path::{Path, PathBuf};
+pub use std::sync::Arc;
 pub use std::time::SystemTime;
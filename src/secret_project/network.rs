@@ -1,17 +1,18 @@
 use crate::secret_project::*;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 pub struct NetworkPlugin;
 
 impl Plugin for NetworkPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup.after(MainStartup));
+        app.init_resource::<NetworkWorker>();
+        app.init_resource::<SyncStatus>();
         app.add_systems(
             Update,
             (
                 debug_log_loading_messages,
-                poll_tasks
-                    .run_if(on_timer(std::time::Duration::from_millis(20)))
-                    .run_if(on_loading_screen),
+                start_sync,
+                poll_tasks.run_if(on_timer(std::time::Duration::from_millis(20))),
             ),
         );
         app.add_message::<NetworkFetch>();
@@ -21,11 +22,85 @@ impl Plugin for NetworkPlugin {
 #[derive(SystemSet, Debug, Hash, Clone, Copy, PartialEq, Eq)]
 pub struct MainStartup;
 
-fn setup(mut commands: Commands, install: Res<Installation>) {
-    commands.insert_resource(NetworkWorker::run(install.clone()));
+#[derive(Resource, Debug, Default, Clone)]
+pub enum SyncStatus {
+    #[default]
+    Idle,
+    Syncing(String),
+    Done(String),
+    Failed(String),
 }
 
-pub fn download_file(url: &str, path: &Path, overwrite: bool) -> Result<u64, VertexError> {
+impl SyncStatus {
+    pub fn label(&self) -> String {
+        match self {
+            SyncStatus::Idle => String::new(),
+            SyncStatus::Syncing(s) => format!("Syncing puzzles... {}", s),
+            SyncStatus::Done(s) => format!("Puzzles up to date ({})", s),
+            SyncStatus::Failed(s) => format!("Offline: {}", s),
+        }
+    }
+}
+
+fn start_sync(
+    mut messages: MessageReader<NetworkFetch>,
+    mut worker: ResMut<NetworkWorker>,
+    mut status: ResMut<SyncStatus>,
+    install: Res<Installation>,
+    settings: Res<Settings>,
+) {
+    if messages.read().count() == 0 {
+        return;
+    }
+    if worker.current_job.is_some() {
+        info!("Already syncing puzzles");
+        return;
+    }
+    let cache = FetchCache::new(install.fetch_cache());
+    let source = parse_source(&settings.puzzle_source, Some(cache));
+    let verifier = ManifestVerifier::from_settings(&settings);
+    *worker = NetworkWorker::run(install.clone(), source, verifier);
+    *status = SyncStatus::Syncing(String::new());
+}
+
+const CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+const DOWNLOAD_ATTEMPTS: u32 = 4;
+const RETRY_BASE_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+// calls f until it succeeds, gives up on an error which won't go away by
+// itself, or runs out of attempts, doubling the delay after each failure
+pub fn with_retries<T>(
+    attempts: u32,
+    base_delay: std::time::Duration,
+    mut f: impl FnMut() -> Result<T, VertexError>,
+) -> Result<T, VertexError> {
+    let mut delay = base_delay;
+    let mut attempt = 1;
+    loop {
+        match f() {
+            Ok(t) => return Ok(t),
+            Err(e) if attempt < attempts && e.is_transient() => {
+                warn!(
+                    "Attempt {} of {} failed, retrying in {:?}: {}",
+                    attempt, attempts, delay, e
+                );
+                std::thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+pub fn download_file(
+    url: &str,
+    path: &Path,
+    overwrite: bool,
+    limit: u64,
+    received: &AtomicU64,
+) -> Result<u64, VertexError> {
     let exists = std::fs::exists(path)?;
     if exists {
         if overwrite {
@@ -37,12 +112,118 @@ pub fn download_file(url: &str, path: &Path, overwrite: bool) -> Result<u64, Ver
     } else {
         info!("Downloading {} to {}", url, path.display());
     }
-    let mut resp = reqwest::blocking::get(url)?.error_for_status()?;
-    if exists {
-        std::fs::remove_file(path)?;
+
+    match download_if_changed(url, path, limit, None, received)? {
+        Download::Fetched { bytes, .. } => Ok(bytes),
+        Download::NotModified => Err("Server sent 304 to an unconditional request".into()),
+    }
+}
+
+pub enum Download {
+    Fetched {
+        bytes: u64,
+        validators: CacheValidators,
+    },
+    // the server says the copy described by the validators is current, and
+    // nothing was written
+    NotModified,
+}
+
+pub fn download_if_changed(
+    url: &str,
+    path: &Path,
+    limit: u64,
+    validators: Option<&CacheValidators>,
+    received: &AtomicU64,
+) -> Result<Download, VertexError> {
+    use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+    use std::io::Read;
+
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+
+    with_retries(DOWNLOAD_ATTEMPTS, RETRY_BASE_DELAY, || {
+        let mut request = client.get(url);
+        if let Some(v) = validators {
+            if let Some(etag) = &v.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &v.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let resp = request.send()?;
+        if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(Download::NotModified);
+        }
+        let resp = resp.error_for_status()?;
+
+        let header = |name| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let validators = CacheValidators {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        };
+
+        if resp.content_length().is_some_and(|len| len > limit) {
+            return Err(IntegrityError::too_large(url, limit));
+        }
+        // truncates whatever a previous attempt left behind
+        let mut file = CountingWriter {
+            inner: std::fs::File::create(path)?,
+            received,
+            written: 0,
+        };
+        // the server may not send a length, or may lie about it
+        let copied = std::io::copy(&mut resp.take(limit.saturating_add(1)), &mut file);
+        match copied {
+            Ok(bytes) if bytes <= limit => Ok(Download::Fetched { bytes, validators }),
+            Ok(_) => {
+                file.forget();
+                Err(IntegrityError::too_large(url, limit))
+            }
+            Err(e) => {
+                file.forget();
+                Err(e.into())
+            }
+        }
+    })
+}
+
+// adds to received as bytes are written, so progress moves during a
+// download rather than only once it's done
+struct CountingWriter<'a, W> {
+    inner: W,
+    received: &'a AtomicU64,
+    written: u64,
+}
+
+impl<W> CountingWriter<'_, W> {
+    // takes back everything written, for an attempt which didn't count
+    fn forget(&mut self) {
+        self.received.fetch_sub(self.written, Ordering::Relaxed);
+        self.written = 0;
+    }
+}
+
+impl<W: std::io::Write> std::io::Write for CountingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.received.fetch_add(n as u64, Ordering::Relaxed);
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
     }
-    let mut file = std::fs::File::create(path)?;
-    Ok(std::io::copy(&mut resp, &mut file)?)
 }
 
 #[allow(unused)]
@@ -52,6 +233,102 @@ pub enum VertexError {
     Serde(serde_yaml::Error),
     IO(std::io::Error),
     Str(String),
+    // a file was written by a later version of the game than this one
+    NewerVersion { found: u32, supported: u32 },
+    // a download wasn't what the manifest said it would be
+    Integrity(IntegrityError),
+}
+
+#[derive(Debug)]
+pub enum IntegrityError {
+    HashMismatch {
+        file: String,
+        expected: String,
+        found: String,
+    },
+    TooLarge {
+        file: String,
+        limit: u64,
+    },
+    Unsigned {
+        file: String,
+    },
+    BadSignature {
+        file: String,
+    },
+}
+
+impl IntegrityError {
+    pub fn too_large(file: impl Into<String>, limit: u64) -> VertexError {
+        VertexError::Integrity(IntegrityError::TooLarge {
+            file: file.into(),
+            limit,
+        })
+    }
+}
+
+impl VertexError {
+    // whether trying again later might work, as with timeouts and dropped
+    // connections, rather than a missing file or a bad puzzle
+    pub fn is_transient(&self) -> bool {
+        use std::io::ErrorKind;
+        match self {
+            VertexError::Reqwest(e) => match e.status() {
+                Some(status) => {
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                }
+                None => e.is_timeout() || (!e.is_builder() && !e.is_decode()),
+            },
+            VertexError::IO(e) => matches!(
+                e.kind(),
+                ErrorKind::TimedOut
+                    | ErrorKind::Interrupted
+                    | ErrorKind::UnexpectedEof
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for VertexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VertexError::Reqwest(e) if e.is_timeout() => write!(f, "timed out"),
+            VertexError::Reqwest(e) if e.is_connect() => write!(f, "couldn't connect"),
+            VertexError::Reqwest(e) => match e.status() {
+                Some(status) => write!(f, "server returned {}", status),
+                None => write!(f, "network error: {}", e),
+            },
+            VertexError::Serde(e) => write!(f, "invalid file: {}", e),
+            VertexError::IO(e) => write!(f, "{}", e),
+            VertexError::Str(s) => write!(f, "{}", s),
+            VertexError::Integrity(IntegrityError::HashMismatch {
+                file,
+                expected,
+                found,
+            }) => write!(
+                f,
+                "{} doesn't match the manifest (expected sha-256 {}, got {})",
+                file, expected, found
+            ),
+            VertexError::Integrity(IntegrityError::TooLarge { file, limit }) => {
+                write!(f, "{} is larger than the limit of {} bytes", file, limit)
+            }
+            VertexError::Integrity(IntegrityError::Unsigned { file }) => {
+                write!(f, "{} isn't signed, and its source isn't trusted", file)
+            }
+            VertexError::Integrity(IntegrityError::BadSignature { file }) => {
+                write!(f, "{} has an invalid signature", file)
+            }
+            VertexError::NewerVersion { found, supported } => write!(
+                f,
+                "needs a newer version of the game (format {}, this version reads up to {})",
+                found, supported
+            ),
+        }
+    }
 }
 
 impl From<reqwest::Error> for VertexError {
@@ -68,6 +345,12 @@ impl From<serde_yaml::Error> for VertexError {
 
 impl From<std::io::Error> for VertexError {
     fn from(value: std::io::Error) -> Self {
+        // reading a response body hands back reqwest's errors wrapped in
+        // io::Error, which would otherwise hide a dropped connection
+        if value.get_ref().is_some_and(|e| e.is::<reqwest::Error>()) {
+            let inner = value.into_inner().expect("get_ref was Some");
+            return Self::Reqwest(*inner.downcast().expect("is was true"));
+        }
         Self::IO(value)
     }
 }
@@ -78,52 +361,40 @@ impl From<&str> for VertexError {
     }
 }
 
-fn do_network_job(install: Installation) -> Result<(), VertexError> {
-    install_remote_manifest(&install, true)?;
-
-    let manifest: Manifest = Manifest::from_file(&install.network_manifest())?;
-
-    for puzzle in manifest.puzzles {
-        info!("Installing {}", puzzle.short_name);
-        match install_puzzle_file(&install, &puzzle.short_name, false) {
-            Ok(_) => {
-                info!("Success!");
-            }
-            Err(e) => {
-                error!("Failed to install: {:?}", e);
-            }
-        }
-    }
-
-    Ok(())
-}
-
-pub const NETWORK_MANIFEST_URL: &'static str =
-    "https://jwade109.github.io/vertex_puzzles/manifest.yaml";
-
-pub fn puzzle_file_url(short_name: &str) -> String {
-    format!(
-        "https://jwade109.github.io/vertex_puzzles/{}/puzzle.txt",
-        short_name
-    )
+fn do_network_job(
+    install: Installation,
+    source: Box<dyn PuzzleSource>,
+    verifier: ManifestVerifier,
+    progress: Arc<SyncProgress>,
+) -> Result<SyncReport, VertexError> {
+    info!("Syncing puzzles from {}", source.describe());
+    sync_packs(&install, source.as_ref(), &verifier, false, &progress)
 }
 
 #[derive(Message)]
 pub struct NetworkFetch;
 
-type NetworkTask = Task<Result<(), VertexError>>;
+type NetworkTask = Task<Result<SyncReport, VertexError>>;
 
 #[derive(Resource, Default)]
 struct NetworkWorker {
     current_job: Option<NetworkTask>,
+    progress: Arc<SyncProgress>,
 }
 
 impl NetworkWorker {
-    fn run(install: Installation) -> Self {
+    fn run(
+        install: Installation,
+        source: Box<dyn PuzzleSource>,
+        verifier: ManifestVerifier,
+    ) -> Self {
+        let progress = Arc::new(SyncProgress::default());
+        let p = progress.clone();
         let thread_pool = AsyncComputeTaskPool::get();
-        let task = thread_pool.spawn(async move { do_network_job(install) });
+        let task = thread_pool.spawn(async move { do_network_job(install, source, verifier, p) });
         Self {
             current_job: Some(task),
+            progress,
         }
     }
 }
@@ -131,23 +402,58 @@ impl NetworkWorker {
 fn poll_tasks(
     mut commands: Commands,
     mut worker: ResMut<NetworkWorker>,
-    mut state: ResMut<NextState<AppState>>,
+    mut status: ResMut<SyncStatus>,
+    mut packs: ResMut<PuzzlePacks>,
+    install: Res<Installation>,
 ) {
-    if let Some(task) = &mut worker.current_job {
-        if let Some(result) = future::block_on(future::poll_once(task)) {
-            match result {
-                Ok(_) => {
-                    info!("Task successful");
-                    commands.write_message(LoadingMessage("Success!".to_string()));
-                    state.set(AppState::Menu);
-                }
-                Err(e) => {
-                    error!("Task failed: {:?}", e);
-                    let s = format!("Failed to fetch puzzles: {:?}", e);
-                    commands.write_message(LoadingMessage(s));
-                }
+    let Some(task) = &mut worker.current_job else {
+        return;
+    };
+    let Some(result) = future::block_on(future::poll_once(task)) else {
+        let s = worker.progress.describe();
+        if !matches!(&*status, SyncStatus::Syncing(p) if *p == s) {
+            commands.write_message(LoadingMessage(s.clone()));
+            *status = SyncStatus::Syncing(s);
+        }
+        return;
+    };
+    worker.current_job = None;
+
+    match result {
+        Ok(report) => {
+            info!("Task successful");
+            commands.write_message(LoadingMessage(report.summary()));
+            *status = SyncStatus::Done(report.summary());
+
+            match PuzzlePacks::from_install(&install) {
+                Ok(new) if new != *packs => *packs = new,
+                Ok(_) => (),
+                Err(e) => error!("Failed to reload manifest: {:?}", e),
+            }
+
+            if !report.added.is_empty() {
+                commands.write_message(TextMessage::info(format!(
+                    "{} new puzzles available!",
+                    report.added.len()
+                )));
+            } else if report.has_changes() {
+                commands.write_message(TextMessage::info(format!(
+                    "Puzzles synced: {}",
+                    report.summary()
+                )));
             }
-            worker.current_job = None;
+
+            if let Some(failures) = report.failure_summary() {
+                commands.write_message(LoadingMessage(failures.clone()));
+                commands.write_message(TextMessage::info(failures));
+            }
+        }
+        Err(e) => {
+            error!("Task failed: {:?}", e);
+            let s = format!("Failed to fetch puzzles: {:?}", e);
+            commands.write_message(LoadingMessage(s));
+            *status = SyncStatus::Failed(e.to_string());
+            commands.write_message(TextMessage::info("Couldn't sync puzzles; playing offline"));
         }
     }
 }
@@ -157,3 +463,57 @@ fn debug_log_loading_messages(mut messages: MessageReader<LoadingMessage>) {
         info!(?msg);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timed_out() -> VertexError {
+        std::io::Error::from(std::io::ErrorKind::TimedOut).into()
+    }
+
+    #[test]
+    fn retries_transient_errors() {
+        let mut calls = 0;
+        let result = with_retries(3, std::time::Duration::ZERO, || {
+            calls += 1;
+            match calls {
+                3 => Ok(calls),
+                _ => Err(timed_out()),
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        let mut calls = 0;
+        let result: Result<(), _> = with_retries(3, std::time::Duration::ZERO, || {
+            calls += 1;
+            Err(timed_out())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn gives_up_on_permanent_errors() {
+        let mut calls = 0;
+        let result: Result<(), _> = with_retries(3, std::time::Duration::ZERO, || {
+            calls += 1;
+            Err("no such puzzle".into())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn unwraps_reqwest_errors_from_body_reads() {
+        let e = reqwest::blocking::get("not a url").unwrap_err();
+        let wrapped = std::io::Error::other(e);
+        assert!(matches!(
+            VertexError::from(wrapped),
+            VertexError::Reqwest(_)
+        ));
+
+        let plain = std::io::Error::other("disk on fire");
+        assert!(matches!(VertexError::from(plain), VertexError::IO(_)));
+    }
+}
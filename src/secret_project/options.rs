@@ -0,0 +1,205 @@
+use crate::secret_project::*;
+
+use bevy::input::InputSystems;
+use bevy::window::{MonitorSelection, PresentMode, WindowMode};
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+pub struct OptionsPlugin;
+
+impl Plugin for OptionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(OptionsMenu::default())
+            .add_systems(PreUpdate, capture_rebind.after(InputSystems))
+            .add_systems(
+                Update,
+                (
+                    save_settings.run_if(on_timer(std::time::Duration::from_secs_f32(0.5))),
+                    apply_window_settings,
+                ),
+            )
+            .add_systems(EguiPrimaryContextPass, options_ui_system);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct OptionsMenu {
+    pub open: bool,
+    // the action waiting for a key to be pressed
+    pub rebinding: Option<Action>,
+}
+
+// runs before anything else reads the keyboard, so the key being bound
+// doesn't also trigger whatever it was bound to before
+fn capture_rebind(
+    mut menu: ResMut<OptionsMenu>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some(action) = menu.rebinding else {
+        return;
+    };
+
+    let Some(key) = keys.get_just_pressed().copied().find(|k| is_bindable(*k)) else {
+        return;
+    };
+
+    // every key can be bound, escape included; the menu's cancel button
+    // backs out instead
+    let binding = Binding::from_input(key, &keys);
+    settings.bindings.set(action, binding);
+    keys.reset(key);
+    menu.rebinding = None;
+}
+
+fn save_settings(settings: Res<Settings>, install: Res<Installation>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let path = install.settings();
+    match save_to_file(&*settings, &path) {
+        Ok(()) => info!("Saved settings to {}", path.display()),
+        Err(e) => error!("Failed to save settings: {:?}", e),
+    }
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    window.mode = if settings.fullscreen {
+        WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+    } else {
+        WindowMode::Windowed
+    };
+
+    window.present_mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+}
+
+fn options_ui_system(
+    mut contexts: EguiContexts,
+    mut settings: ResMut<Settings>,
+    mut menu: ResMut<OptionsMenu>,
+    mut mouse: ResMut<CursorState>,
+) {
+    let ctx = match contexts.ctx_mut() {
+        Ok(ctx) => ctx,
+        Err(_) => return,
+    };
+
+    // the editor panel also keeps this up to date, but isn't always shown
+    mouse.on_egui = ctx.is_pointer_over_area();
+
+    if !menu.open {
+        return;
+    }
+
+    // edit a copy, so that merely showing the menu doesn't count as a change
+    let mut edited = settings.clone();
+    let mut open = menu.open;
+
+    egui::Window::new("Options")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.style_mut().visuals.dark_mode = false;
+
+            ui.label("Volume");
+            ui.add(egui::Slider::new(&mut edited.volume, 0.0..=1.0));
+
+            ui.label("Vertex Size");
+            ui.add(egui::Slider::new(&mut edited.vertex_size, 0.5..=3.0));
+
+            ui.checkbox(&mut edited.show_hints, "Show Hints");
+            ui.checkbox(&mut edited.fullscreen, "Fullscreen");
+            ui.checkbox(&mut edited.vsync, "VSync");
+
+            ui.label("Puzzle Source (used on next sync)");
+            ui.text_edit_singleline(&mut edited.puzzle_source);
+
+            ui.label("Manifest Key (blank for the built in key)");
+            let mut key = edited.manifest_key.clone().unwrap_or_default();
+            if ui.text_edit_singleline(&mut key).changed() {
+                let key = key.trim();
+                edited.manifest_key = (!key.is_empty()).then(|| key.to_string());
+            }
+
+            let source = edited.puzzle_source.clone();
+            let mut trusted = edited.trusted_sources.contains(&source);
+            if ui
+                .checkbox(&mut trusted, "Trust this source without a signature")
+                .changed()
+            {
+                edited.trusted_sources.retain(|s| *s != source);
+                if trusted {
+                    edited.trusted_sources.push(source);
+                }
+            }
+
+            ui.separator();
+            key_bindings_ui(ui, &mut edited.bindings, &mut menu.rebinding);
+        });
+
+    if edited != *settings {
+        *settings = edited;
+    }
+
+    if !open {
+        menu.open = false;
+        menu.rebinding = None;
+    }
+}
+
+fn key_bindings_ui(ui: &mut egui::Ui, bindings: &mut KeyBindings, rebinding: &mut Option<Action>) {
+    let conflicts = bindings.conflicts();
+
+    ui.collapsing("Key Bindings", |ui| {
+        egui::Grid::new("key_bindings")
+            .striped(true)
+            .show(ui, |ui| {
+                for action in all::<Action>() {
+                    ui.label(action.label());
+
+                    let text = if *rebinding == Some(action) {
+                        egui::RichText::new("Press a key...")
+                    } else if conflicts.contains(&action) {
+                        egui::RichText::new(bindings.get(action).to_string())
+                            .color(egui::Color32::RED)
+                    } else {
+                        egui::RichText::new(bindings.get(action).to_string())
+                    };
+
+                    if ui.button(text).clicked() {
+                        *rebinding = match *rebinding {
+                            Some(a) if a == action => None,
+                            _ => Some(action),
+                        };
+                    }
+                    if *rebinding == Some(action) && ui.button("Cancel").clicked() {
+                        *rebinding = None;
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if !conflicts.is_empty() {
+            ui.colored_label(
+                egui::Color32::RED,
+                "Some keys are bound to more than one action",
+            );
+        }
+
+        if ui.button("Reset to Defaults").clicked() {
+            bindings.reset();
+            *rebinding = None;
+        }
+    });
+}
@@ -0,0 +1,239 @@
+use crate::secret_project::*;
+
+// the puzzles listed directly in the source's manifest, installed where
+// puzzles were before there were packs
+pub const DEFAULT_PACK: &'static str = "main";
+
+// identifies a puzzle regardless of where it sits in its manifest
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PuzzleKey {
+    pub pack: String,
+    pub short_name: String,
+}
+
+impl PuzzleKey {
+    pub fn new(pack: impl Into<String>, short_name: impl Into<String>) -> Self {
+        Self {
+            pack: pack.into(),
+            short_name: short_name.into(),
+        }
+    }
+
+    // "pack/short_name", or just the short name for the default pack
+    pub fn parse(s: &str) -> Self {
+        match s.split_once('/') {
+            Some((pack, short_name)) => Self::new(pack, short_name),
+            None => Self::new(DEFAULT_PACK, s),
+        }
+    }
+}
+
+impl std::fmt::Display for PuzzleKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.pack.as_str() {
+            DEFAULT_PACK => write!(f, "{}", self.short_name),
+            pack => write!(f, "{}/{}", pack, self.short_name),
+        }
+    }
+}
+
+// a pack listed in the source's manifest, which has a manifest of its own
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackInfo {
+    pub name: String,
+    pub title: String,
+    // a url or directory, or a path relative to the source the pack is
+    // listed in; the pack's name if unset
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+// pack and puzzle names become directory names, so anything else is
+// refused
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn is_valid_pack_name(name: &str) -> bool {
+    is_valid_name(name) && name != DEFAULT_PACK
+}
+
+pub fn pack_source(
+    parent: &dyn PuzzleSource,
+    info: &PackInfo,
+    cache: Option<FetchCache>,
+) -> Box<dyn PuzzleSource> {
+    match &info.source {
+        Some(s) if s.contains("://") || s.starts_with('/') => parse_source(s, cache),
+        Some(s) => parent.join(s),
+        None => parent.join(&info.name),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pack {
+    pub name: String,
+    pub title: String,
+    pub manifest: Manifest,
+}
+
+// every installed pack, the default one first
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct PuzzlePacks(pub Vec<Pack>);
+
+impl PuzzlePacks {
+    // packs which haven't been synced yet, or whose manifests don't load,
+    // are left out rather than failing the whole load
+    pub fn from_install(install: &Installation) -> Result<Self, VertexError> {
+        let root = Manifest::from_file(&install.network_manifest())?;
+
+        let mut packs = vec![Pack {
+            name: DEFAULT_PACK.to_string(),
+            title: "Main".to_string(),
+            manifest: root.clone(),
+        }];
+
+        for info in root.packs.iter().filter(|p| is_valid_pack_name(&p.name)) {
+            let path = install.pack(&info.name).network_manifest();
+            match Manifest::from_file(&path) {
+                Ok(manifest) => packs.push(Pack {
+                    name: info.name.clone(),
+                    title: info.title.clone(),
+                    manifest,
+                }),
+                Err(e) => warn!("Skipping pack {}: {}", info.name, e),
+            }
+        }
+
+        Ok(Self(packs))
+    }
+
+    pub fn pack(&self, name: &str) -> Option<&Pack> {
+        self.0.iter().find(|p| p.name == name)
+    }
+
+    pub fn get(&self, key: &PuzzleKey) -> Option<&PuzzleManifestInfo> {
+        self.pack(&key.pack)?.manifest.find(&key.short_name)
+    }
+
+    // where the puzzle is listed in its pack
+    pub fn position(&self, key: &PuzzleKey) -> Option<usize> {
+        let pack = self.pack(&key.pack)?;
+        pack.manifest
+            .puzzles
+            .iter()
+            .position(|p| p.short_name == key.short_name)
+    }
+
+    // the puzzle listed offset places after this one, in the same pack
+    pub fn neighbor(&self, key: &PuzzleKey, offset: isize) -> Option<PuzzleKey> {
+        let pack = self.pack(&key.pack)?;
+        let i = self.position(key)?.checked_add_signed(offset)?;
+        let info = pack.manifest.puzzles.get(i)?;
+        Some(PuzzleKey::new(&pack.name, &info.short_name))
+    }
+}
+
+// how many of a pack's puzzles have ever been completed, out of how many
+pub fn pack_progress(install: &Installation, pack: &Pack) -> (usize, usize) {
+    let install = install.pack(&pack.name);
+    let complete = pack
+        .manifest
+        .puzzles
+        .iter()
+        .filter(|info| {
+            SaveData::from_file(&install.save_data_file(&info.short_name))
+                .is_ok_and(|save| save.was_ever_complete)
+        })
+        .count();
+    (complete, pack.manifest.puzzles.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packs() -> PuzzlePacks {
+        let manifest = |names: &[&str]| Manifest {
+            puzzles: names
+                .iter()
+                .map(|n| PuzzleManifestInfo::new(*n, *n))
+                .collect(),
+            ..default()
+        };
+        PuzzlePacks(vec![
+            Pack {
+                name: DEFAULT_PACK.to_string(),
+                title: "Main".to_string(),
+                manifest: manifest(&["rose", "fox"]),
+            },
+            Pack {
+                name: "winter".to_string(),
+                title: "Winter".to_string(),
+                manifest: manifest(&["snow", "rose"]),
+            },
+        ])
+    }
+
+    #[test]
+    fn keys_round_trip() {
+        for s in ["rose", "winter/rose"] {
+            assert_eq!(PuzzleKey::parse(s).to_string(), s);
+        }
+        assert_eq!(PuzzleKey::parse("rose").pack, DEFAULT_PACK);
+    }
+
+    #[test]
+    fn lookups_use_the_pack() {
+        let packs = packs();
+        assert_eq!(packs.position(&PuzzleKey::parse("winter/rose")), Some(1));
+        assert_eq!(packs.position(&PuzzleKey::parse("rose")), Some(0));
+        assert!(packs.get(&PuzzleKey::parse("winter/fox")).is_none());
+    }
+
+    #[test]
+    fn neighbors_stay_in_the_pack() {
+        let packs = packs();
+        let rose = PuzzleKey::parse("rose");
+        assert_eq!(packs.neighbor(&rose, 1), Some(PuzzleKey::parse("fox")));
+        assert_eq!(packs.neighbor(&rose, -1), None);
+        let fox = PuzzleKey::parse("fox");
+        assert_eq!(packs.neighbor(&fox, 1), None);
+    }
+
+    #[test]
+    fn pack_names_are_checked() {
+        assert!(is_valid_pack_name("winter-2025"));
+        assert!(!is_valid_pack_name("../etc"));
+        assert!(!is_valid_pack_name(DEFAULT_PACK));
+        assert!(!is_valid_pack_name(""));
+        assert!(is_valid_name("rose_2"));
+        assert!(!is_valid_name(".."));
+        assert!(!is_valid_name("a/b"));
+    }
+
+    #[test]
+    fn pack_sources_are_relative_by_default() {
+        let parent = DirectorySource::new("/srv/puzzles");
+        let describe = |source: Option<&str>| {
+            let info = PackInfo {
+                name: "winter".to_string(),
+                title: "Winter".to_string(),
+                source: source.map(str::to_string),
+            };
+            pack_source(&parent, &info, None).describe()
+        };
+        assert_eq!(describe(None), "file:///srv/puzzles/winter");
+        assert_eq!(
+            describe(Some("extra/winter")),
+            "file:///srv/puzzles/extra/winter"
+        );
+        assert_eq!(
+            describe(Some("https://example.com/w")),
+            "https://example.com/w"
+        );
+    }
+}
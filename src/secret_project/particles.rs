@@ -6,7 +6,11 @@ impl Plugin for ParticlePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (add_particles, update_ripples, update_grid_particles),
+            (
+                add_particles.run_if(not(in_state(AppState::Menu))),
+                update_ripples,
+                update_grid_particles,
+            ),
         );
     }
 }
@@ -27,6 +31,7 @@ fn add_particles(
     mut commands: Commands,
     keys: Res<ButtonInput<KeyCode>>,
     cursor: Res<CursorState>,
+    app: Res<Settings>,
 ) {
     let p = if let Some(p) = cursor.get() {
         p
@@ -34,11 +39,11 @@ fn add_particles(
         return;
     };
 
-    if keys.just_pressed(KeyCode::KeyF) {
+    if app.bindings.just_pressed(Action::Ripple, &keys) {
         commands.spawn(Ripple(p, 0.0));
     }
 
-    if keys.just_pressed(KeyCode::KeyG) {
+    if app.bindings.just_pressed(Action::GridParticle, &keys) {
         let v = random(1200.0, 2000.0);
         let a = random(0.0, 2.0 * std::f32::consts::PI);
         let v = Vec2::from_angle(a) * v;
@@ -0,0 +1,257 @@
+use crate::secret_project::*;
+
+// the ids of a face's corners in counterclockwise order, starting from
+// the smallest id
+pub type FaceKey = Vec<usize>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Face {
+    pub color: Srgba,
+}
+
+impl Face {
+    pub fn new(color: Srgba) -> Self {
+        Self { color }
+    }
+}
+
+// pairs of edges which cross each other somewhere other than a shared vertex
+pub fn crossing_edges(positions: &HashMap<usize, Vec2>, edges: &Edges) -> HashSet<(usize, usize)> {
+    let segments: Vec<((usize, usize), Vec2, Vec2)> = edges
+        .0
+        .iter()
+        .filter_map(|(a, b)| Some(((*a, *b), *positions.get(a)?, *positions.get(b)?)))
+        .collect();
+
+    let mut crossing = HashSet::new();
+    for i in 0..segments.len() {
+        let (e1, a, b) = segments[i];
+        let (min1, max1) = (a.min(b), a.max(b));
+        for (e2, c, d) in &segments[(i + 1)..] {
+            let (min2, max2) = (c.min(*d), c.max(*d));
+            if max1.x < min2.x || max2.x < min1.x || max1.y < min2.y || max2.y < min1.y {
+                continue;
+            }
+            if segments_cross(a, b, *c, *d) {
+                crossing.insert(e1);
+                crossing.insert(*e2);
+            }
+        }
+    }
+    crossing
+}
+
+pub fn signed_area(points: &[Vec2]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| points[i].perp_dot(points[(i + 1) % n]))
+        .sum::<f32>()
+        / 2.0
+}
+
+// orders a loop of vertex ids the way face keys expect
+pub fn canonical_face(ids: &[usize], positions: &HashMap<usize, Vec2>) -> Option<FaceKey> {
+    let points = ids
+        .iter()
+        .map(|id| positions.get(id).copied())
+        .collect::<Option<Vec<Vec2>>>()?;
+
+    let mut key = ids.to_vec();
+    if signed_area(&points) < 0.0 {
+        key.reverse();
+    }
+
+    let start = key.iter().enumerate().min_by_key(|(_, id)| **id)?.0;
+    key.rotate_left(start);
+    Some(key)
+}
+
+// drops edges which the boundary walks out along and straight back, such
+// as an edge dangling into the face, leaving the loop around them
+fn strip_spurs(face: &mut Vec<usize>) {
+    loop {
+        let n = face.len();
+        if n < 3 {
+            return;
+        }
+
+        let Some(i) = (0..n).find(|i| face[(i + n - 1) % n] == face[(i + 1) % n]) else {
+            return;
+        };
+        let j = (i + 1) % n;
+        face.remove(i.max(j));
+        face.remove(i.min(j));
+    }
+}
+
+// traces the bounded faces of a planar straight-line graph. each face is
+// returned as a counterclockwise loop of vertex ids. edges which cross
+// other edges should be left out, since they don't have a planar embedding.
+pub fn find_faces(positions: &HashMap<usize, Vec2>, edges: &Edges) -> Vec<FaceKey> {
+    // neighbors of every vertex, sorted counterclockwise
+    let mut neighbors: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (a, b) in &edges.0 {
+        if !positions.contains_key(a) || !positions.contains_key(b) {
+            continue;
+        }
+        neighbors.entry(*a).or_default().push(*b);
+        neighbors.entry(*b).or_default().push(*a);
+    }
+
+    for (v, list) in neighbors.iter_mut() {
+        let center = positions[v];
+        list.sort_by(|p, q| {
+            let dp = positions[p] - center;
+            let dq = positions[q] - center;
+            dp.y.atan2(dp.x).total_cmp(&dq.y.atan2(dq.x))
+        });
+    }
+
+    let mut visited: HashSet<(usize, usize)> = HashSet::new();
+    let mut faces = Vec::new();
+
+    let mut half_edges: Vec<(usize, usize)> = neighbors
+        .iter()
+        .flat_map(|(u, list)| list.iter().map(move |v| (*u, *v)))
+        .collect();
+    half_edges.sort();
+
+    for start in half_edges {
+        if visited.contains(&start) {
+            continue;
+        }
+
+        let mut face = Vec::new();
+        let (mut u, mut v) = start;
+        loop {
+            visited.insert((u, v));
+            face.push(u);
+
+            // leaving v, turn as far right as possible from where we came
+            // in; this keeps the face on the left of every half edge
+            let list = &neighbors[&v];
+            let i = list.iter().position(|w| *w == u).unwrap();
+            let w = list[(i + list.len() - 1) % list.len()];
+
+            u = v;
+            v = w;
+            if (u, v) == start {
+                break;
+            }
+        }
+
+        strip_spurs(&mut face);
+
+        // what's left can still pass through a vertex twice where two parts
+        // of the face meet at a single point. it's kept, but can't be drawn
+        // properly as a single polygon
+        let unique: HashSet<&usize> = face.iter().collect();
+        if unique.len() != face.len() {
+            warn!("Face {:?} touches itself at a vertex", face);
+        }
+
+        let points: Vec<Vec2> = face.iter().map(|id| positions[id]).collect();
+        if signed_area(&points) > 0.0 {
+            let start = face
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, id)| **id)
+                .unwrap()
+                .0;
+            face.rotate_left(start);
+            faces.push(face);
+        }
+    }
+
+    faces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(points: &[(f32, f32)], edges: &[(usize, usize)]) -> (HashMap<usize, Vec2>, Edges) {
+        let positions = points
+            .iter()
+            .enumerate()
+            .map(|(i, (x, y))| (i, Vec2::new(*x, *y)))
+            .collect();
+        let mut e = Edges::default();
+        for (a, b) in edges {
+            e.add_edge(*a, *b);
+        }
+        (positions, e)
+    }
+
+    #[test]
+    fn square_with_diagonal() {
+        let (p, e) = graph(
+            &[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)],
+            &[(0, 1), (1, 2), (2, 3), (3, 0), (0, 2)],
+        );
+        let faces = find_faces(&p, &e);
+        assert_eq!(faces.len(), 2);
+        assert!(faces.iter().all(|f| f.len() == 3));
+        assert!(faces.contains(&vec![0, 1, 2]));
+        assert!(faces.contains(&vec![0, 2, 3]));
+    }
+
+    #[test]
+    fn quad_face() {
+        let (p, e) = graph(
+            &[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)],
+            &[(0, 1), (1, 2), (2, 3), (3, 0)],
+        );
+        assert_eq!(find_faces(&p, &e), vec![vec![0, 1, 2, 3]]);
+        assert_eq!(canonical_face(&[2, 1, 0, 3], &p), Some(vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn enclosing_triangle_is_not_a_face() {
+        // a big triangle with a vertex in the middle connected to every
+        // corner; the outer triangle is a 3-clique but not a face
+        let (p, e) = graph(
+            &[(0.0, 0.0), (20.0, 0.0), (10.0, 20.0), (10.0, 7.0)],
+            &[(0, 1), (1, 2), (2, 0), (0, 3), (1, 3), (2, 3)],
+        );
+        let mut faces = find_faces(&p, &e);
+        assert_eq!(faces.len(), 3);
+        for f in &mut faces {
+            f.sort();
+        }
+        assert!(!faces.contains(&vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn dangling_edges_are_ignored() {
+        let (p, e) = graph(
+            &[(0.0, 0.0), (10.0, 0.0), (0.0, 10.0), (-10.0, -10.0)],
+            &[(0, 1), (1, 2), (2, 0), (0, 3)],
+        );
+        let faces = find_faces(&p, &e);
+        assert_eq!(faces.len(), 1);
+    }
+
+    #[test]
+    fn pinched_faces_are_kept() {
+        // the edge 0-3 dangles into the triangle, so the walk around the
+        // face goes out to 3 and back
+        let (p, e) = graph(
+            &[(0.0, 0.0), (20.0, 0.0), (0.0, 20.0), (5.0, 5.0)],
+            &[(0, 1), (1, 2), (2, 0), (0, 3)],
+        );
+        assert_eq!(find_faces(&p, &e), vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn detects_crossings() {
+        let (p, e) = graph(
+            &[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)],
+            &[(0, 2), (1, 3), (0, 1)],
+        );
+        let crossing = crossing_edges(&p, &e);
+        assert_eq!(crossing.len(), 2);
+        assert!(crossing.contains(&(0, 2)));
+        assert!(crossing.contains(&(1, 3)));
+    }
+}
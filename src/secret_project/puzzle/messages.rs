@@ -19,11 +19,16 @@ impl Plugin for PuzzleMessagePlugin {
                     on_add_vertex,
                     on_quantize,
                     on_toggle_edge,
-                ),
+                )
+                    .in_set(PuzzleEdits),
             );
     }
 }
 
+// every system which applies edit messages to the puzzle
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PuzzleEdits;
+
 #[derive(Message, Debug)]
 pub struct AddVertex(pub Vec2);
 
@@ -42,9 +47,16 @@ pub struct ToggleEdge(pub usize, pub usize);
 #[derive(Message, Debug)]
 pub struct Quantize(pub u16);
 
-fn on_add_vertex(mut puzzle: Single<&mut Puzzle>, mut messages: MessageReader<AddVertex>) {
+fn on_add_vertex(
+    mut puzzle: Single<&mut Puzzle>,
+    mut save: ResMut<SaveData>,
+    mut history: ResMut<EditHistory>,
+    mut messages: MessageReader<AddVertex>,
+) {
     for msg in messages.read() {
-        puzzle.add_point(msg.0);
+        history.record("Add vertex", &mut puzzle, &mut save, |p, _| {
+            p.add_point(msg.0);
+        });
     }
 }
 
@@ -52,25 +64,31 @@ fn on_delete_vertex(
     mut commands: Commands,
     mut puzzle: Single<&mut Puzzle>,
     mut save: ResMut<SaveData>,
+    mut history: ResMut<EditHistory>,
     mut messages: MessageReader<DeleteVertex>,
 ) {
     for msg in messages.read() {
         if let Some(v) = puzzle.vertex_n(msg.0) {
             commands.spawn(Ripple::new(v.pos));
         }
-        puzzle.remove_vertex(msg.0, &mut save);
+        history.record("Delete vertex", &mut puzzle, &mut save, |p, save| {
+            p.remove_vertex(msg.0, save)
+        });
     }
 }
 
 fn on_add_edge(
     mut puzzle: Single<&mut Puzzle>,
     mut save: ResMut<SaveData>,
+    mut history: ResMut<EditHistory>,
     mut messages: MessageReader<AddEdge>,
     state: Res<State<AppState>>,
 ) {
     for msg in messages.read() {
         if state.is_editor() {
-            puzzle.add_solution_edge(msg.0, msg.1);
+            history.record("Add edge", &mut puzzle, &mut save, |p, _| {
+                p.add_solution_edge(msg.0, msg.1)
+            });
         } else {
             puzzle.add_game_edge(msg.0, msg.1, &mut save);
         }
@@ -81,11 +99,14 @@ fn on_delete_edge(
     mut puzzle: Single<&mut Puzzle>,
     mut messages: MessageReader<DeleteEdge>,
     mut save: ResMut<SaveData>,
+    mut history: ResMut<EditHistory>,
     state: Res<State<AppState>>,
 ) {
     for msg in messages.read() {
         if state.is_editor() {
-            puzzle.remove_edge(msg.0, msg.1);
+            history.record("Delete edge", &mut puzzle, &mut save, |p, _| {
+                p.remove_edge(msg.0, msg.1)
+            });
         } else {
             save.edges.remove_edge(msg.0, msg.1);
         }
@@ -95,17 +116,31 @@ fn on_delete_edge(
 fn on_toggle_edge(
     mut puzzle: Single<&mut Puzzle>,
     mut save: ResMut<SaveData>,
+    mut history: ResMut<EditHistory>,
     mut messages: MessageReader<ToggleEdge>,
     state: Res<State<AppState>>,
 ) {
     let is_play = !state.is_editor();
     for msg in messages.read() {
-        puzzle.toggle_edge(&mut save, msg.0, msg.1, is_play);
+        if is_play {
+            puzzle.toggle_edge(&mut save, msg.0, msg.1, true);
+        } else {
+            history.record("Toggle edge", &mut puzzle, &mut save, |p, save| {
+                p.toggle_edge(save, msg.0, msg.1, false)
+            });
+        }
     }
 }
 
-fn on_quantize(mut puzzle: Single<&mut Puzzle>, mut messages: MessageReader<Quantize>) {
+fn on_quantize(
+    mut puzzle: Single<&mut Puzzle>,
+    mut save: ResMut<SaveData>,
+    mut history: ResMut<EditHistory>,
+    mut messages: MessageReader<Quantize>,
+) {
     for msg in messages.read() {
-        puzzle.quantize_colors(msg.0);
+        history.record("Quantize", &mut puzzle, &mut save, |p, _| {
+            p.quantize_colors(msg.0)
+        });
     }
 }
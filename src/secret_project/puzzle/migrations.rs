@@ -0,0 +1,145 @@
+use crate::secret_project::*;
+use serde_yaml::{Mapping, Value};
+
+// bump this, and add a step to MIGRATIONS, whenever PuzzleFileStorage
+// changes shape
+pub const PUZZLE_VERSION: u32 = 1;
+
+type Migration = fn(&mut Mapping) -> Result<(), VertexError>;
+
+// MIGRATIONS[n] upgrades a version n file to version n + 1
+const MIGRATIONS: [Migration; PUZZLE_VERSION as usize] = [triangles_to_faces];
+
+// version 0 stored colored triangles as [a, b, c, color];
+// version 1 stores polygon faces as [[a, b, c, ...], color]
+fn triangles_to_faces(map: &mut Mapping) -> Result<(), VertexError> {
+    let triangles = match map.remove("triangles") {
+        Some(Value::Sequence(seq)) => seq,
+        Some(Value::Null) | None => Vec::new(),
+        Some(_) => return Err("Expected a list of triangles".into()),
+    };
+
+    // files saved between polygon faces and versioning can have both
+    let mut faces = match map.remove("faces") {
+        Some(Value::Sequence(seq)) => seq,
+        Some(Value::Null) | None => Vec::new(),
+        Some(_) => return Err("Expected a list of faces".into()),
+    };
+    for t in triangles {
+        let mut t = match t {
+            Value::Sequence(t) if t.len() == 4 => t,
+            _ => return Err("Expected a triangle as [a, b, c, color]".into()),
+        };
+        let color = t.pop().unwrap();
+        faces.push(Value::Sequence(vec![Value::Sequence(t), color]));
+    }
+
+    map.insert("faces".into(), Value::Sequence(faces));
+    Ok(())
+}
+
+fn file_version(map: &Mapping) -> Result<u32, VertexError> {
+    match map.get("version") {
+        None => Ok(0),
+        Some(v) => {
+            let v = v.as_u64().ok_or("Puzzle version isn't a number")?;
+            u32::try_from(v).map_err(|_| "Puzzle version is out of range".into())
+        }
+    }
+}
+
+// brings a puzzle file written by any earlier version of the game up to
+// the current schema
+pub fn migrate_puzzle(value: Value) -> Result<Value, VertexError> {
+    let mut map = match value {
+        Value::Mapping(map) => map,
+        _ => return Err("Expected a puzzle file to be a mapping".into()),
+    };
+
+    let found = file_version(&map)?;
+    if found > PUZZLE_VERSION {
+        return Err(VertexError::NewerVersion {
+            found,
+            supported: PUZZLE_VERSION,
+        });
+    }
+
+    for step in &MIGRATIONS[found as usize..] {
+        step(&mut map)?;
+    }
+
+    if found < PUZZLE_VERSION {
+        info!(
+            "Migrated puzzle from version {} to {}",
+            found, PUZZLE_VERSION
+        );
+    }
+
+    map.insert("version".into(), PUZZLE_VERSION.into());
+    Ok(Value::Mapping(map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V0: &str = "
+vertices:
+  0: [0.0, 0.0]
+  1: [10.0, 0.0]
+  2: [0.0, 10.0]
+edges: [[0, 1], [1, 2], [0, 2]]
+triangles:
+  - [0, 1, 2, {red: 1.0, green: 0.5, blue: 0.0, alpha: 1.0}]
+reference_images: []
+";
+
+    #[test]
+    fn upgrades_unversioned_files() {
+        let value: Value = serde_yaml::from_str(V0).unwrap();
+        let value = migrate_puzzle(value).unwrap();
+        let repr: PuzzleFileStorage = serde_yaml::from_value(value).unwrap();
+        assert_eq!(repr.version, PUZZLE_VERSION);
+        assert_eq!(repr.faces.len(), 1);
+        assert_eq!(repr.faces[0].0, vec![0, 1, 2]);
+        assert_eq!(repr.faces[0].1, Srgba::new(1.0, 0.5, 0.0, 1.0));
+    }
+
+    #[test]
+    fn keeps_faces_alongside_triangles() {
+        let yaml = format!(
+            "{}faces:\n  - [[0, 1, 2], {{red: 0.0, green: 0.0, blue: 1.0, alpha: 1.0}}]\n",
+            V0
+        );
+        let value: Value = serde_yaml::from_str(&yaml).unwrap();
+        let value = migrate_puzzle(value).unwrap();
+        let repr: PuzzleFileStorage = serde_yaml::from_value(value).unwrap();
+        assert_eq!(repr.faces.len(), 2);
+    }
+
+    #[test]
+    fn rejects_huge_versions() {
+        let value: Value = serde_yaml::from_str("version: 4294967296").unwrap();
+        assert!(migrate_puzzle(value).is_err());
+    }
+
+    #[test]
+    fn current_files_are_unchanged() {
+        let repr = PuzzleFileStorage {
+            version: PUZZLE_VERSION,
+            faces: vec![(vec![0, 1, 2, 3], Srgba::RED)],
+            ..default()
+        };
+        let value = serde_yaml::to_value(&repr).unwrap();
+        assert_eq!(migrate_puzzle(value.clone()).unwrap(), value);
+    }
+
+    #[test]
+    fn rejects_newer_files() {
+        let value: Value = serde_yaml::from_str("version: 9999").unwrap();
+        assert!(matches!(
+            migrate_puzzle(value),
+            Err(VertexError::NewerVersion { found: 9999, .. })
+        ));
+    }
+}
@@ -1,13 +1,19 @@
 mod edge;
+mod faces;
 mod messages;
+mod migrations;
 mod plugin;
 mod puzzle;
-mod triangle;
+mod snapshot;
+mod solver;
 mod vertex;
 
 pub use edge::*;
+pub use faces::*;
 pub use messages::*;
+pub use migrations::*;
 pub use plugin::*;
 pub use puzzle::*;
-pub use triangle::*;
+pub use snapshot::*;
+pub use solver::*;
 pub use vertex::*;
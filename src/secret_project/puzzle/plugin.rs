@@ -9,6 +9,7 @@ impl Plugin for PuzzlePlugin {
             (
                 update_cursor_vertex_info.run_if(is_editor_or_playing),
                 get_rel_cursor_info,
+                update_vertex_hints.before(draw_vertices),
                 draw_vertices,
                 update_title.run_if(is_playing),
                 draw_vertex_cursor_info.run_if(camera_is_moveable),
@@ -18,12 +19,14 @@ impl Plugin for PuzzlePlugin {
                     .run_if(is_playing)
                     .run_if(on_timer(std::time::Duration::from_secs_f32(0.1))),
                 detect_win_condition.run_if(is_playing),
+                flush_game_progress_on_leave,
                 // experimental animated vertex stuff
                 // update_animated_vertices,
                 // draw_animated_vertices,
                 // nudge_vertices,
             ),
         );
+        app.add_systems(Last, flush_game_progress_on_exit);
         app.insert_resource(CursorVertexInfo::default());
     }
 }
@@ -58,6 +61,7 @@ fn update_cursor_vertex_info(
     cursor: Res<CursorState>,
     puzzle: Single<&Puzzle>,
     buttons: Res<ButtonInput<MouseButton>>,
+    state: Res<State<AppState>>,
 ) {
     if let Some(pos) = cursor.get() {
         let r = 50.0 * camera.scale.x;
@@ -66,7 +70,13 @@ fn update_cursor_vertex_info(
         vinfo.hovered = None;
     }
 
-    if buttons.just_pressed(MouseButton::Left) {
+    // in move mode, dragging from a vertex moves it instead of drawing an edge
+    let is_move = **state
+        == AppState::Editing {
+            mode: EditorMode::Move,
+        };
+
+    if buttons.just_pressed(MouseButton::Left) && !is_move {
         vinfo.clicked = vinfo.hovered;
     }
 
@@ -86,6 +96,16 @@ fn update_cursor_vertex_info(
     }
 }
 
+fn update_vertex_hints(mut puzzle: Single<&mut Puzzle>, save: Res<SaveData>) {
+    if !puzzle.is_changed() && !save.is_changed() {
+        return;
+    }
+
+    // hint counts are derived from the puzzle, so updating them shouldn't
+    // count as a change to the puzzle itself
+    puzzle.bypass_change_detection().update_hints(&save);
+}
+
 fn draw_vertex_cursor_info(
     mut painter: ShapePainter,
     vinfo: Res<CursorVertexInfo>,
@@ -117,13 +137,18 @@ pub fn draw_solution_edges(
     mut painter: ShapePainter,
     puzzle: Single<&Puzzle>,
     keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
 ) {
-    if keys.pressed(KeyCode::KeyV) {
+    if settings.bindings.pressed(Action::HideVertices, &keys) {
         return;
     }
     for (_, a, _, b) in puzzle.solution_edges() {
         draw_line(&mut painter, a.pos, b.pos, SOLUTION_EDGES_Z, 1.0, BLACK);
     }
+    // crossing edges don't bound any face, so point them out
+    for (a, b) in puzzle.crossing_edges() {
+        draw_line(&mut painter, a.pos, b.pos, SOLUTION_EDGES_Z, 3.0, RED);
+    }
 }
 
 fn draw_game_edges(mut painter: ShapePainter, puzzle: Single<&Puzzle>, save: Res<SaveData>) {
@@ -135,38 +160,72 @@ fn draw_game_edges(mut painter: ShapePainter, puzzle: Single<&Puzzle>, save: Res
     }
 }
 
+fn write_game_progress(
+    text: &mut MessageWriter<TextMessage>,
+    save: &SaveData,
+    current: &CurrentPuzzle,
+    install: &Installation,
+) {
+    let key = match &current.0 {
+        Some(key) => key,
+        _ => return,
+    };
+
+    let path = install.pack(&key.pack).save_data_file(&key.short_name);
+
+    if let Err(e) = save_to_file(save, &path) {
+        error!("Failed to save: {:?}", e);
+        text.write(TextMessage::info("Failed to autosave :("));
+    } else {
+        info!("Autosaved to {}", path.display());
+    }
+}
+
 fn autosave_game_progress(
     mut text: MessageWriter<TextMessage>,
     save: Res<SaveData>,
     current: Res<CurrentPuzzle>,
-    manifest: Res<Manifest>,
     install: Res<Installation>,
 ) {
     if !save.is_changed() {
         return;
     }
 
-    let id = match current.0 {
-        Some(id) => id,
-        _ => return,
-    };
-
-    let info = match manifest.get(id) {
-        Some(info) => info,
-        _ => return,
-    };
+    info!("Puzzle has been changed since last autosave");
 
-    let path = install.save_data_file(&info.short_name);
+    write_game_progress(&mut text, &save, &current, &install);
+}
 
-    info!("Puzzle has been changed since last autosave");
+// the autosave timer may not have fired since the last move, so write
+// progress out on the way back to the menu
+fn flush_game_progress_on_leave(
+    mut tr: MessageReader<StateTransitionEvent<AppState>>,
+    mut text: MessageWriter<TextMessage>,
+    save: Res<SaveData>,
+    current: Res<CurrentPuzzle>,
+    install: Res<Installation>,
+) {
+    let left_puzzle = tr.read().any(|msg| {
+        let was_playing = msg.exited.is_some_and(|s| s.has_game_progress());
+        was_playing && msg.entered.is_some_and(|s| s.is_menu())
+    });
 
-    let save_data: &SaveData = &*save;
+    if left_puzzle {
+        write_game_progress(&mut text, &save, &current, &install);
+    }
+}
 
-    if let Err(e) = save_to_file(save_data, &path) {
-        error!("Failed to save: {:?}", e);
-        text.write(TextMessage::info("Failed to autosave :("));
-    } else {
-        info!("Autosaved to {}", path.display());
+// ...and before the app closes
+fn flush_game_progress_on_exit(
+    mut exit: MessageReader<AppExit>,
+    mut text: MessageWriter<TextMessage>,
+    state: Res<State<AppState>>,
+    save: Res<SaveData>,
+    current: Res<CurrentPuzzle>,
+    install: Res<Installation>,
+) {
+    if exit.read().count() > 0 && state.has_game_progress() {
+        write_game_progress(&mut text, &save, &current, &install);
     }
 }
 
@@ -255,13 +314,13 @@ fn nudge_vertices(
         _ => return,
     };
 
-    let (a, b, c) = match puzzle.triangle_at(pos) {
+    let face = match puzzle.face_at(pos) {
         Some(x) => x,
         _ => return,
     };
 
     for mut v in vertices {
-        if v.index != a && v.index != b && v.index != c {
+        if !face.contains(&v.index) {
             continue;
         }
 
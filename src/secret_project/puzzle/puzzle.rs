@@ -9,7 +9,8 @@ pub struct Puzzle {
     next_vertex_id: usize,
     vertices: HashMap<usize, Vertex>,
     pub solution_edges: Edges,
-    triangles: HashMap<(usize, usize, usize), Triangle>,
+    faces: HashMap<FaceKey, Face>,
+    crossing_edges: HashSet<(usize, usize)>,
 }
 
 fn random_color() -> Srgba {
@@ -42,15 +43,15 @@ impl Puzzle {
     }
 
     pub fn update(&mut self) {
-        self.update_triangles();
+        self.update_faces();
     }
 
     pub fn quantize_colors(&mut self, n_colors: u16) {
         let mut indices = Vec::new();
         let mut lab = Vec::new();
-        for (k, triangle) in self.triangles.iter() {
-            let l = srgba_to_rgb(triangle.color);
-            indices.push(*k);
+        for (k, face) in self.faces.iter() {
+            let l = srgba_to_rgb(face.color);
+            indices.push(k.clone());
             lab.push(l);
         }
 
@@ -75,10 +76,9 @@ impl Puzzle {
         }
 
         for (i, centroid_id) in result.indices.iter().enumerate() {
-            let k = indices[i];
-            let triangle = self.triangles.get_mut(&k).unwrap();
+            let face = self.faces.get_mut(&indices[i]).unwrap();
             let color = rgb_to_srgba(result.centroids[*centroid_id as usize].clone());
-            triangle.color = color;
+            face.color = color;
         }
 
         // Convert indexed colors back to Srgb<u8> for output
@@ -90,36 +90,38 @@ impl Puzzle {
         // let buffer = Srgb::map_indices_to_centroids(&rgb, &result.indices);
     }
 
-    fn update_triangles(&mut self) {
-        for (u, v) in &self.solution_edges.0 {
-            let u = *u;
-            let v = *v;
-            for (w, _) in &self.vertices {
-                let w = *w;
-                if u >= v || v >= w {
-                    continue;
-                }
+    fn update_faces(&mut self) {
+        let positions = self.positions();
 
-                let key = (u, v, w);
+        self.crossing_edges = crossing_edges(&positions, &self.solution_edges);
 
-                if self.solution_edges.is_edge(v, w) && self.solution_edges.is_edge(u, w) {
-                    if self.triangles.contains_key(&key) {
-                        continue;
-                    }
-                    let color = random_color();
-                    let t = Triangle::new(color);
-                    self.triangles.insert(key, t);
-                }
-            }
+        let mut planar = self.solution_edges.clone();
+        planar.0.retain(|e| !self.crossing_edges.contains(e));
+
+        let mut faces = HashMap::new();
+        for key in find_faces(&positions, &planar) {
+            // faces which survive an edit keep their colour
+            let face = self
+                .faces
+                .get(&key)
+                .copied()
+                .unwrap_or_else(|| Face::new(random_color()));
+            faces.insert(key, face);
         }
 
-        let mut triangles = self.triangles.clone();
-        triangles.retain(|(a, b, c), _| {
-            self.solution_edges.is_edge(*a, *b)
-                && self.solution_edges.is_edge(*a, *c)
-                && self.solution_edges.is_edge(*b, *c)
-        });
-        self.triangles = triangles;
+        self.faces = faces;
+    }
+
+    fn positions(&self) -> HashMap<usize, Vec2> {
+        self.vertices.iter().map(|(id, v)| (*id, v.pos)).collect()
+    }
+
+    pub fn crossing_edges(&self) -> impl Iterator<Item = (&Vertex, &Vertex)> + use<'_> {
+        self.crossing_edges.iter().filter_map(|(a, b)| {
+            let v1 = self.vertex_n(*a)?;
+            let v2 = self.vertex_n(*b)?;
+            Some((v1, v2))
+        })
     }
 
     fn next_vertex_id(&mut self) -> usize {
@@ -128,7 +130,7 @@ impl Puzzle {
         r
     }
 
-    pub fn add_point(&mut self, p: Vec2) {
+    pub fn add_point(&mut self, p: Vec2) -> usize {
         let id = self.next_vertex_id();
         self.vertices.insert(id, Vertex::new(p));
         // if let Some((other, pos)) = with_active_edge.then(|| active_line.0).flatten() {
@@ -160,6 +162,8 @@ impl Puzzle {
         // }
 
         // self.update();
+
+        id
     }
 
     pub fn triangulate(&mut self, sel: Res<SelectedVertices>) {
@@ -224,6 +228,69 @@ impl Puzzle {
         self.vertices.iter().map(|(i, v)| (*i, v))
     }
 
+    pub fn hints(&self) -> Vec<VertexHint> {
+        let mut degrees: HashMap<usize, usize> = HashMap::new();
+        for (a, b) in &self.solution_edges.0 {
+            *degrees.entry(*a).or_default() += 1;
+            *degrees.entry(*b).or_default() += 1;
+        }
+
+        let mut hints: Vec<VertexHint> = self
+            .vertices()
+            .map(|(id, v)| VertexHint {
+                id,
+                pos: v.pos,
+                degree: degrees.get(&id).copied().unwrap_or(0),
+            })
+            .collect();
+        hints.sort_by_key(|h| h.id);
+        hints
+    }
+
+    pub fn analyze_solutions(&self) -> SolutionAnalysis {
+        analyze_solutions(self.hints(), &self.solution_edges)
+    }
+
+    pub fn snapshot(&self, save: &SaveData) -> PuzzleSnapshot {
+        PuzzleSnapshot {
+            vertices: self.vertices.iter().map(|(id, v)| (*id, v.pos)).collect(),
+            edges: self.solution_edges.0.clone(),
+            faces: self
+                .faces
+                .iter()
+                .map(|(k, f)| (k.clone(), f.color))
+                .collect(),
+            save_edges: save.edges.0.clone(),
+        }
+    }
+
+    // used when replaying history, so ids must be kept exactly as recorded
+    pub fn set_vertex(&mut self, id: usize, pos: Option<Vec2>) {
+        match pos {
+            Some(pos) => {
+                self.vertices
+                    .entry(id)
+                    .and_modify(|v| v.pos = pos)
+                    .or_insert_with(|| Vertex::new(pos));
+                self.next_vertex_id = self.next_vertex_id.max(id + 1);
+            }
+            None => {
+                self.vertices.remove(&id);
+            }
+        }
+    }
+
+    pub fn set_face(&mut self, key: FaceKey, color: Option<Srgba>) {
+        match color {
+            Some(color) => {
+                self.faces.insert(key, Face::new(color));
+            }
+            None => {
+                self.faces.remove(&key);
+            }
+        }
+    }
+
     pub fn vertex_n(&self, n: usize) -> Option<&Vertex> {
         self.vertices.get(&n)
     }
@@ -269,32 +336,35 @@ impl Puzzle {
         })
     }
 
-    pub fn triangles<'a>(
+    pub fn faces<'a>(
         &'a self,
         save: &'a SaveData,
         is_play: bool,
-    ) -> impl Iterator<Item = (Vec2, Vec2, Vec2, Srgba)> + use<'a> {
-        self.triangles.iter().filter_map(move |((a, b, c), t)| {
-            if !self.solution_edges.is_edge(*a, *b)
-                || !self.solution_edges.is_edge(*a, *c)
-                || !self.solution_edges.is_edge(*b, *c)
+    ) -> impl Iterator<Item = (Vec<Vec2>, Srgba)> + use<'a> {
+        self.faces_with_progress(save)
+            .filter(move |(_, _, revealed)| !is_play || *revealed)
+            .map(|(points, color, _)| (points, color))
+    }
+
+    // every solution face, and whether the player has revealed it yet
+    pub fn faces_with_progress<'a>(
+        &'a self,
+        save: &'a SaveData,
+    ) -> impl Iterator<Item = (Vec<Vec2>, Srgba, bool)> + use<'a> {
+        self.faces.iter().filter_map(move |(key, f)| {
+            let n = key.len();
+            let sides = (0..n).map(|i| (key[i], key[(i + 1) % n]));
+
+            if !sides
+                .clone()
+                .all(|(a, b)| self.solution_edges.is_edge(a, b))
             {
                 return None;
             }
 
-            if is_play {
-                if !save.edges.is_edge(*a, *b)
-                    || !save.edges.is_edge(*a, *c)
-                    || !save.edges.is_edge(*b, *c)
-                {
-                    return None;
-                }
-            }
-
-            let a = self.vertex_n(*a)?.pos;
-            let b = self.vertex_n(*b)?.pos;
-            let c = self.vertex_n(*c)?.pos;
-            Some((a, b, c, t.color))
+            let revealed = sides.clone().all(|(a, b)| save.edges.is_edge(a, b));
+            let points = self.face_points(key)?;
+            Some((points, f.color, revealed))
         })
     }
 
@@ -303,13 +373,13 @@ impl Puzzle {
         self.vertices.remove_entry(&id);
         self.solution_edges.remove_vertex(id);
         save.edges.remove_vertex(id);
-        self.update_triangles();
+        self.update_faces();
     }
 
     pub fn add_solution_edge(&mut self, a: usize, b: usize) {
         info!("Adding solution edge between {} and {}", a, b);
         self.solution_edges.add_edge(a, b);
-        self.update_triangles();
+        self.update_faces();
     }
 
     pub fn add_game_edge(&mut self, a: usize, b: usize, save: &mut SaveData) {
@@ -320,7 +390,7 @@ impl Puzzle {
     pub fn remove_edge(&mut self, a: usize, b: usize) {
         info!("Adding solution edge between {} and {}", a, b);
         self.solution_edges.remove_edge(a, b);
-        self.update_triangles();
+        self.update_faces();
     }
 
     pub fn toggle_edge(&mut self, save: &mut SaveData, a: usize, b: usize, is_play: bool) {
@@ -328,31 +398,44 @@ impl Puzzle {
             save.edges.toggle(a, b);
         } else {
             self.solution_edges.toggle(a, b);
-            self.update_triangles();
+            self.update_faces();
         }
     }
 
-    pub fn triangle_at(&self, p: Vec2) -> Option<(usize, usize, usize)> {
-        self.triangles.iter().find_map(|((a, b, c), _)| {
-            let pa = self.vertices.get(a)?.pos;
-            let pb = self.vertices.get(b)?.pos;
-            let pc = self.vertices.get(c)?.pos;
-            point_in_triangle(p, pa, pb, pc).then(|| (*a, *b, *c))
-        })
+    pub fn translate_vertex(&mut self, id: usize, delta: Vec2) {
+        if let Some(v) = self.vertices.get_mut(&id) {
+            v.pos += delta;
+        }
+    }
+
+    pub fn face_keys(&self) -> Vec<FaceKey> {
+        self.faces.keys().cloned().collect()
+    }
+
+    pub fn faces_touching(&self, ids: &HashSet<usize>) -> Vec<FaceKey> {
+        self.faces
+            .keys()
+            .filter(|key| key.iter().any(|id| ids.contains(id)))
+            .cloned()
+            .collect()
     }
 
-    pub fn get_triangle_at(&mut self, p: Vec2) -> Option<&mut Triangle> {
-        self.triangles.iter_mut().find_map(|((a, b, c), t)| {
-            let a = self.vertices.get(a)?.pos;
-            let b = self.vertices.get(b)?.pos;
-            let c = self.vertices.get(c)?.pos;
-            point_in_triangle(p, a, b, c).then(|| t)
+    pub fn face_points(&self, key: &[usize]) -> Option<Vec<Vec2>> {
+        key.iter()
+            .map(|id| Some(self.vertices.get(id)?.pos))
+            .collect()
+    }
+
+    pub fn face_at(&self, p: Vec2) -> Option<FaceKey> {
+        self.faces.keys().find_map(|key| {
+            let points = self.face_points(key)?;
+            point_in_polygon(p, &points).then(|| key.clone())
         })
     }
 
-    pub fn set_triangle_color(&mut self, p: Vec2, color: Srgba) {
-        if let Some(t) = self.get_triangle_at(p) {
-            t.color = color;
+    pub fn set_face_color(&mut self, p: Vec2, color: Srgba) {
+        if let Some(key) = self.face_at(p) {
+            self.set_face(key, Some(color));
         }
     }
 
@@ -360,9 +443,41 @@ impl Puzzle {
         self.solution_edges.0 == save.edges.0
     }
 
+    pub fn update_hints(&mut self, save: &SaveData) {
+        for v in self.vertices.values_mut() {
+            v.visible_count = 0;
+            v.invisible_count = 0;
+            v.wrong_count = 0;
+        }
+
+        for (a, b) in &self.solution_edges.0 {
+            let placed = save.edges.is_edge(*a, *b);
+            for id in [a, b] {
+                if let Some(v) = self.vertices.get_mut(id) {
+                    if placed {
+                        v.visible_count += 1;
+                    } else {
+                        v.invisible_count += 1;
+                    }
+                }
+            }
+        }
+
+        for (a, b) in &save.edges.0 {
+            if self.solution_edges.is_edge(*a, *b) {
+                continue;
+            }
+            for id in [a, b] {
+                if let Some(v) = self.vertices.get_mut(id) {
+                    v.wrong_count += 1;
+                }
+            }
+        }
+    }
+
     pub fn progress(&self, save: &SaveData) -> f32 {
-        let n_sol = self.triangles(save, false).count();
-        let n_game = self.triangles(save, true).count();
+        let n_sol = self.faces(save, false).count();
+        let n_game = self.faces(save, true).count();
         if n_sol == 0 {
             return 0.0;
         }
@@ -372,9 +487,11 @@ impl Puzzle {
 
 #[derive(Deserialize, Serialize, Default, Debug)]
 pub struct PuzzleFileStorage {
+    #[serde(default)]
+    pub version: u32,
     pub vertices: HashMap<usize, Vec2>,
     pub edges: Vec<(usize, usize)>,
-    pub triangles: Vec<(usize, usize, usize, Srgba)>,
+    pub faces: Vec<(Vec<usize>, Srgba)>,
     pub reference_images: Vec<ReferenceImage>,
 }
 
@@ -394,25 +511,32 @@ pub fn repr_to_puzzle(value: PuzzleFileStorage) -> (Puzzle, Vec<ReferenceImage>)
         puzzle.solution_edges.add_edge(a, b);
     }
 
-    for (a, b, c, color) in value.triangles {
-        puzzle.triangles.insert((a, b, c), Triangle::new(color));
+    let positions = puzzle.positions();
+    for (ids, color) in value.faces {
+        if let Some(key) = canonical_face(&ids, &positions) {
+            puzzle.faces.insert(key, Face::new(color));
+        }
     }
 
     puzzle.next_vertex_id = max_id + 1;
+    puzzle.update_faces();
 
     (puzzle, value.reference_images)
 }
 
 fn puzzle_to_repr(value: &Puzzle, images: Vec<ReferenceImage>) -> PuzzleFileStorage {
-    let mut repr = PuzzleFileStorage::default();
+    let mut repr = PuzzleFileStorage {
+        version: PUZZLE_VERSION,
+        ..default()
+    };
     for (id, p) in &value.vertices {
         repr.vertices.insert(*id, p.pos);
     }
     for (a, b) in &value.solution_edges.0 {
         repr.edges.push((*a, *b));
     }
-    for ((a, b, c), t) in &value.triangles {
-        repr.triangles.push((*a, *b, *c, t.color));
+    for (key, f) in &value.faces {
+        repr.faces.push((key.clone(), f.color));
     }
 
     repr.reference_images = images;
@@ -436,10 +560,28 @@ pub fn puzzle_from_file(
 ) -> Result<(Puzzle, Vec<ReferenceImage>), VertexError> {
     let filepath = filepath.into();
     info!("Loading puzzle at {}", filepath.display());
-    let repr: PuzzleFileStorage = load_from_file(&filepath)?;
+    let value: serde_yaml::Value = load_from_file(&filepath)?;
+    let repr: PuzzleFileStorage = serde_yaml::from_value(migrate_puzzle(value)?)?;
     Ok(repr_to_puzzle(repr))
 }
 
+// even-odd rule, so it works for any simple polygon
+pub fn point_in_polygon(test: Vec2, points: &[Vec2]) -> bool {
+    let n = points.len();
+    let mut inside = false;
+    for i in 0..n {
+        let a = points[i];
+        let b = points[(i + 1) % n];
+        if (a.y > test.y) != (b.y > test.y) {
+            let x = a.x + (test.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if test.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
 pub fn point_in_triangle(test: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
     let alpha = ((b.y - c.y) * (test.x - c.x) + (c.x - b.x) * (test.y - c.y))
         / ((b.y - c.y) * (a.x - c.x) + (c.x - b.x) * (a.y - c.y));
@@ -456,12 +598,13 @@ pub fn draw_vertices(
     camera: Single<&Transform, With<Camera>>,
     state: Res<State<AppState>>,
     keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
 ) {
-    let scale = camera.scale.x;
+    let scale = camera.scale.x * settings.vertex_size;
 
     let is_play = !state.is_editor();
 
-    if state.is_editor() && keys.pressed(KeyCode::KeyV) {
+    if state.is_editor() && settings.bindings.pressed(Action::HideVertices, &keys) {
         return;
     }
 
@@ -482,12 +625,13 @@ pub fn draw_vertices(
                 WHITE,
             );
 
-            let total_edges = v.invisible_count + v.visible_count;
-            for i in 0..total_edges {
-                let color = if i < v.invisible_count { BLACK } else { GRAY };
-                let r = 20.0 * scale;
-                let a = std::f32::consts::PI * (0.5 + 2.0 * i as f32 / total_edges as f32);
-                let p = v.pos + Vec2::from_angle(a) * r;
+            if !settings.show_hints {
+                continue;
+            }
+
+            let total_edges = v.invisible_count + v.visible_count + v.wrong_count;
+            for (i, color) in v.hint_colors().enumerate() {
+                let p = v.pos + hint_dot_offset(i, total_edges, 20.0 * scale);
                 fill_circle(&mut painter, p, VERTEX_Z_2, 4.0 * scale, color);
             }
         } else {
@@ -531,33 +675,44 @@ pub fn load_from_file<T: for<'a> Deserialize<'a>>(path: &Path) -> Result<T, Vert
     Ok(val)
 }
 
-pub fn open_puzzle_by_id(
+pub fn open_puzzle_by_key(
     mut commands: Commands,
-    manifest: Res<Manifest>,
+    packs: Res<PuzzlePacks>,
     install: Res<Installation>,
     all_windows: Query<Entity, With<RefImageWindow>>,
     mut puzzle: Single<&mut Puzzle>,
     mut save_data: ResMut<SaveData>,
-    mut msg: MessageReader<OpenPuzzleById>,
+    mut msg: MessageReader<OpenPuzzleByKey>,
     mut open: ResMut<CurrentPuzzle>,
     mut title: Single<&mut RevealedText, With<UiTitle>>,
     mut number: Query<&mut Text, With<UiNumberLabel>>,
+    mut history: ResMut<EditHistory>,
 ) {
     for msg in msg.read() {
         for e in all_windows {
             commands.entity(e).despawn();
         }
-        let id = msg.0;
+        let key = &msg.0;
 
-        let info = match manifest.get(id) {
-            Some(info) => info,
-            _ => continue,
+        let (Some(info), Some(position)) = (packs.get(key), packs.position(key)) else {
+            continue;
         };
 
+        let install = install.pack(&key.pack);
         let path = install.puzzle_file(&info.short_name);
 
         let (p, images) = match puzzle_from_file(&path) {
             Ok((p, images)) => (p, images),
+            Err(VertexError::NewerVersion { found, supported }) => {
+                error!(
+                    "Puzzle is version {}, but this build only supports up to {}",
+                    found, supported
+                );
+                commands.write_message(TextMessage::info(
+                    "This puzzle needs a newer version of the game",
+                ));
+                continue;
+            }
             Err(e) => {
                 let s = format!("{:?}", e);
                 commands.write_message(TextMessage::info(s));
@@ -566,6 +721,7 @@ pub fn open_puzzle_by_id(
         };
 
         **puzzle = p;
+        history.clear();
 
         let save_data_path = install.save_data_file(&info.short_name);
 
@@ -582,7 +738,7 @@ pub fn open_puzzle_by_id(
         *save_data = save;
 
         for mut number in &mut number {
-            number.0 = format!("#{}", id);
+            number.0 = format!("#{}", position);
         }
 
         commands.write_message(TextMessage::debug(format!(
@@ -600,7 +756,7 @@ pub fn open_puzzle_by_id(
             // });
         }
 
-        open.0 = Some(id);
+        open.0 = Some(key.clone());
 
         commands.write_message(SoundEffect::UiThreePop);
     }
@@ -609,9 +765,9 @@ pub fn open_puzzle_by_id(
 pub fn generate_mesh(puzzle: &Puzzle, save: &SaveData, is_play: bool) -> Option<Mesh> {
     let mut builder = MeshMaker::default();
 
-    for (a, b, c, color) in puzzle.triangles(&save, is_play) {
+    for (points, color) in puzzle.faces(&save, is_play) {
         builder.set_color(color.into());
-        builder.triangle([a, b, c]);
+        builder.polygon(&points);
     }
 
     (!builder.is_empty()).then(|| builder.build())
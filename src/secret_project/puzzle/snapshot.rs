@@ -0,0 +1,96 @@
+use crate::secret_project::*;
+use std::hash::Hash;
+
+// everything about a puzzle which the editor can change
+#[derive(Debug, Clone, Default)]
+pub struct PuzzleSnapshot {
+    pub vertices: HashMap<usize, Vec2>,
+    pub edges: HashSet<(usize, usize)>,
+    pub faces: HashMap<FaceKey, Srgba>,
+    // the player's edges, since removing a vertex takes its edges with it
+    pub save_edges: HashSet<(usize, usize)>,
+}
+
+// the entries of a snapshot which differ between two points in time,
+// stored as (key, before, after)
+#[derive(Debug, Clone, Default)]
+pub struct PuzzleDiff {
+    vertices: Vec<(usize, Option<Vec2>, Option<Vec2>)>,
+    edges: Vec<((usize, usize), bool, bool)>,
+    faces: Vec<(FaceKey, Option<Srgba>, Option<Srgba>)>,
+    save_edges: Vec<((usize, usize), bool, bool)>,
+}
+
+fn diff_maps<K: Hash + Eq + Clone, V: PartialEq + Copy>(
+    before: &HashMap<K, V>,
+    after: &HashMap<K, V>,
+) -> Vec<(K, Option<V>, Option<V>)> {
+    let mut changes = Vec::new();
+    for (k, v) in before {
+        let new = after.get(k).copied();
+        if new != Some(*v) {
+            changes.push((k.clone(), Some(*v), new));
+        }
+    }
+    for (k, v) in after {
+        if !before.contains_key(k) {
+            changes.push((k.clone(), None, Some(*v)));
+        }
+    }
+    changes
+}
+
+fn diff_sets(
+    before: &HashSet<(usize, usize)>,
+    after: &HashSet<(usize, usize)>,
+) -> Vec<((usize, usize), bool, bool)> {
+    let removed = before.difference(after).map(|e| (*e, true, false));
+    let added = after.difference(before).map(|e| (*e, false, true));
+    removed.chain(added).collect()
+}
+
+impl PuzzleDiff {
+    pub fn between(before: &PuzzleSnapshot, after: &PuzzleSnapshot) -> Self {
+        Self {
+            vertices: diff_maps(&before.vertices, &after.vertices),
+            edges: diff_sets(&before.edges, &after.edges),
+            faces: diff_maps(&before.faces, &after.faces),
+            save_edges: diff_sets(&before.save_edges, &after.save_edges),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+            && self.edges.is_empty()
+            && self.faces.is_empty()
+            && self.save_edges.is_empty()
+    }
+
+    pub fn apply(&self, puzzle: &mut Puzzle, save: &mut SaveData, forward: bool) {
+        for (id, before, after) in &self.vertices {
+            puzzle.set_vertex(*id, if forward { *after } else { *before });
+        }
+
+        for ((a, b), before, after) in &self.edges {
+            let present = if forward { *after } else { *before };
+            if present {
+                puzzle.solution_edges.add_edge(*a, *b);
+            } else {
+                puzzle.solution_edges.remove_edge(*a, *b);
+            }
+        }
+
+        for (key, before, after) in &self.faces {
+            puzzle.set_face(key.clone(), if forward { *after } else { *before });
+        }
+
+        for ((a, b), before, after) in &self.save_edges {
+            let present = if forward { *after } else { *before };
+            if present {
+                save.edges.add_edge(*a, *b);
+            } else {
+                save.edges.remove_edge(*a, *b);
+            }
+        }
+    }
+}
@@ -0,0 +1,781 @@
+use crate::secret_project::*;
+
+// the solver only ever looks at what the player can see: where the
+// vertices are, and how many edges each one needs.
+#[derive(Debug, Clone, Copy)]
+pub struct VertexHint {
+    pub id: usize,
+    pub pos: Vec2,
+    pub degree: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SolverConfig {
+    // edges are only considered between a vertex and its nearest neighbors
+    pub neighbors: usize,
+    // closes wedges between neighboring edges, but only once the hints
+    // show that the solution triangulates every vertex
+    pub triangle_closure: bool,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            neighbors: 10,
+            triangle_closure: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    // the vertex already has all of its edges
+    Saturated,
+    // the vertex needs every edge it has left
+    Forced,
+    // the edge would cross an edge which is known to exist
+    Crossing,
+    // two edges with nothing between them bound a triangle, since the
+    // solution is a full triangulation
+    TriangleClosure,
+    // not a deduction; used while searching the space of solutions
+    Assumption,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deduction {
+    pub edge: (usize, usize),
+    pub include: bool,
+    pub rule: Rule,
+}
+
+impl std::fmt::Display for Deduction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verb = if self.include { "include" } else { "exclude" };
+        write!(
+            f,
+            "{} {}-{} ({:?})",
+            verb, self.edge.0, self.edge.1, self.rule
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverOutcome {
+    Solved,
+    Stuck,
+    Contradiction,
+}
+
+#[derive(Debug, Clone)]
+pub struct SolverResult {
+    pub outcome: SolverOutcome,
+    pub deductions: Vec<Deduction>,
+    pub edges: Edges,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeState {
+    Unknown,
+    In,
+    Out,
+}
+
+struct Contradiction;
+
+struct SearchBudgetExceeded;
+
+const MAX_SEARCH_NODES: usize = 100_000;
+
+#[derive(Debug, Clone)]
+pub enum SolutionAnalysis {
+    Unique,
+    // carries a solution which differs from the authored one
+    Multiple(Edges),
+    Unsolvable,
+    // the search gave up before exploring every possibility
+    Inconclusive,
+    // the authored solution has an edge the solver can't place, such as
+    // one running straight through another vertex
+    Unknown,
+}
+
+impl std::fmt::Display for SolutionAnalysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unique => write!(f, "Unique"),
+            Self::Multiple(edges) => write!(
+                f,
+                "Multiple solutions (alternate has {} edges)",
+                edges.0.len()
+            ),
+            Self::Unsolvable => write!(f, "Unsolvable"),
+            Self::Inconclusive => write!(f, "Inconclusive (search limit reached)"),
+            Self::Unknown => write!(f, "Unknown (solution has edges the solver can't place)"),
+        }
+    }
+}
+
+pub struct HintSolver {
+    hints: Vec<VertexHint>,
+    candidates: Vec<(usize, usize)>,
+    lookup: HashMap<(usize, usize), usize>,
+    incident: Vec<Vec<usize>>,
+    crossings: Vec<Vec<usize>>,
+    // whether the hints ask for as many edges as the vertices can take,
+    // which makes every face inside the convex hull a triangle
+    triangulation: bool,
+    state: Vec<EdgeState>,
+    deductions: Vec<Deduction>,
+    config: SolverConfig,
+}
+
+impl HintSolver {
+    pub fn new(hints: Vec<VertexHint>, config: SolverConfig) -> Self {
+        let n = hints.len();
+
+        // a vertex with no edges can be left out entirely
+        let mut pairs = HashSet::new();
+        for i in (0..n).filter(|i| hints[*i].degree > 0) {
+            let mut others: Vec<usize> =
+                (0..n).filter(|j| *j != i && hints[*j].degree > 0).collect();
+            others.sort_by(|a, b| {
+                let da = hints[i].pos.distance_squared(hints[*a].pos);
+                let db = hints[i].pos.distance_squared(hints[*b].pos);
+                da.total_cmp(&db)
+            });
+            for j in others.into_iter().take(config.neighbors) {
+                pairs.insert((i.min(j), i.max(j)));
+            }
+        }
+
+        // an edge can't pass straight through another vertex
+        let mut candidates: Vec<(usize, usize)> = pairs
+            .into_iter()
+            .filter(|(i, j)| {
+                let a = hints[*i].pos;
+                let b = hints[*j].pos;
+                !hints
+                    .iter()
+                    .enumerate()
+                    .any(|(k, h)| k != *i && k != *j && distance_to_segment(h.pos, a, b) < 0.01)
+            })
+            .collect();
+        candidates.sort();
+
+        let mut lookup = HashMap::new();
+        let mut incident = vec![Vec::new(); n];
+        for (c, (i, j)) in candidates.iter().enumerate() {
+            lookup.insert((*i, *j), c);
+            incident[*i].push(c);
+            incident[*j].push(c);
+        }
+
+        // only edges whose x ranges overlap can cross, so each is compared
+        // with those starting before it ends, in order of where they start
+        let span = |c: usize| {
+            let (i, j) = candidates[c];
+            let (a, b) = (hints[i].pos.x, hints[j].pos.x);
+            (a.min(b), a.max(b))
+        };
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.sort_by(|a, b| span(*a).0.total_cmp(&span(*b).0));
+
+        let mut crossings = vec![Vec::new(); candidates.len()];
+        for (k, c1) in order.iter().enumerate() {
+            let (i, j) = candidates[*c1];
+            let (a, b) = (hints[i].pos, hints[j].pos);
+            for c2 in &order[k + 1..] {
+                if span(*c2).0 > span(*c1).1 {
+                    break;
+                }
+                let (k, l) = candidates[*c2];
+                if segments_cross(a, b, hints[k].pos, hints[l].pos) {
+                    crossings[*c1].push(*c2);
+                    crossings[*c2].push(*c1);
+                }
+            }
+        }
+
+        let state = vec![EdgeState::Unknown; candidates.len()];
+        let triangulation = is_full_triangulation(&hints);
+
+        Self {
+            hints,
+            candidates,
+            lookup,
+            incident,
+            crossings,
+            triangulation,
+            state,
+            deductions: Vec::new(),
+            config,
+        }
+    }
+
+    fn hint_index(&self) -> HashMap<usize, usize> {
+        self.hints
+            .iter()
+            .enumerate()
+            .map(|(i, h)| (h.id, i))
+            .collect()
+    }
+
+    // whether every one of these edges is among the candidates
+    pub fn covers(&self, edges: &Edges) -> bool {
+        let index = self.hint_index();
+        edges
+            .0
+            .iter()
+            .all(|(a, b)| match (index.get(a), index.get(b)) {
+                (Some(i), Some(j)) => self.lookup.contains_key(&(*i.min(j), *i.max(j))),
+                _ => false,
+            })
+    }
+
+    fn edge_ids(&self, c: usize) -> (usize, usize) {
+        let (i, j) = self.candidates[c];
+        normalize_edge(self.hints[i].id, self.hints[j].id)
+    }
+
+    fn set(&mut self, c: usize, include: bool, rule: Rule) -> Result<(), Contradiction> {
+        let new_state = if include {
+            EdgeState::In
+        } else {
+            EdgeState::Out
+        };
+        match self.state[c] {
+            EdgeState::Unknown => (),
+            s if s == new_state => return Ok(()),
+            _ => return Err(Contradiction),
+        }
+
+        self.state[c] = new_state;
+        self.deductions.push(Deduction {
+            edge: self.edge_ids(c),
+            include,
+            rule,
+        });
+
+        if include {
+            for other in self.crossings[c].clone() {
+                self.set(other, false, Rule::Crossing)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn count(&self, vertex: usize, state: EdgeState) -> usize {
+        self.incident[vertex]
+            .iter()
+            .filter(|c| self.state[**c] == state)
+            .count()
+    }
+
+    fn other_end(&self, c: usize, vertex: usize) -> usize {
+        let (i, j) = self.candidates[c];
+        if i == vertex {
+            j
+        } else {
+            i
+        }
+    }
+
+    fn apply_degree_rules(&mut self) -> Result<bool, Contradiction> {
+        let mut progress = false;
+        for v in 0..self.hints.len() {
+            let placed = self.count(v, EdgeState::In);
+            let unknown = self.count(v, EdgeState::Unknown);
+            let degree = self.hints[v].degree;
+
+            if placed > degree || placed + unknown < degree {
+                return Err(Contradiction);
+            }
+
+            if unknown == 0 {
+                continue;
+            }
+
+            let (include, rule) = if placed == degree {
+                (false, Rule::Saturated)
+            } else if placed + unknown == degree {
+                (true, Rule::Forced)
+            } else {
+                continue;
+            };
+
+            for c in self.incident[v].clone() {
+                if self.state[c] == EdgeState::Unknown {
+                    self.set(c, include, rule)?;
+                }
+            }
+            progress = true;
+        }
+        Ok(progress)
+    }
+
+    // in a full triangulation, two edges at a vertex with no possible edge
+    // between them, less than a half turn apart, are two sides of a
+    // triangle. only candidates can be edges, so candidates must include
+    // every edge the solution could have
+    fn apply_triangle_closure(&mut self) -> Result<bool, Contradiction> {
+        if !self.triangulation {
+            return Ok(false);
+        }
+
+        let mut progress = false;
+        for v in 0..self.hints.len() {
+            let center = self.hints[v].pos;
+            let mut around: Vec<(f32, usize, EdgeState)> = self.incident[v]
+                .iter()
+                .filter(|c| self.state[**c] != EdgeState::Out)
+                .map(|c| {
+                    let other = self.other_end(*c, v);
+                    let d = self.hints[other].pos - center;
+                    (d.y.atan2(d.x), other, self.state[*c])
+                })
+                .collect();
+            if around.len() < 2 {
+                continue;
+            }
+            around.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            for k in 0..around.len() {
+                let (a0, b, s0) = around[k];
+                let (a1, c, s1) = around[(k + 1) % around.len()];
+                if s0 != EdgeState::In || s1 != EdgeState::In {
+                    continue;
+                }
+
+                let mut wedge = a1 - a0;
+                if wedge <= 0.0 {
+                    wedge += 2.0 * std::f32::consts::PI;
+                }
+                if wedge >= std::f32::consts::PI {
+                    continue;
+                }
+
+                let key = (b.min(c), b.max(c));
+                let closing = match self.lookup.get(&key) {
+                    Some(closing) => *closing,
+                    None => continue,
+                };
+
+                if self.state[closing] != EdgeState::Unknown {
+                    continue;
+                }
+
+                self.set(closing, true, Rule::TriangleClosure)?;
+                progress = true;
+            }
+        }
+        Ok(progress)
+    }
+
+    fn propagate(&mut self) -> Result<(), Contradiction> {
+        loop {
+            if self.apply_degree_rules()? {
+                continue;
+            }
+            if self.config.triangle_closure && self.apply_triangle_closure()? {
+                continue;
+            }
+            return Ok(());
+        }
+    }
+
+    fn is_solved(&self) -> bool {
+        (0..self.hints.len()).all(|v| self.count(v, EdgeState::In) == self.hints[v].degree)
+    }
+
+    fn included_edges(&self) -> Edges {
+        let mut edges = Edges::default();
+        for c in 0..self.candidates.len() {
+            if self.state[c] == EdgeState::In {
+                let (a, b) = self.edge_ids(c);
+                edges.add_edge(a, b);
+            }
+        }
+        edges
+    }
+
+    pub fn solve(mut self) -> SolverResult {
+        let outcome = match self.propagate() {
+            Err(Contradiction) => SolverOutcome::Contradiction,
+            Ok(()) if self.is_solved() => SolverOutcome::Solved,
+            Ok(()) => SolverOutcome::Stuck,
+        };
+
+        SolverResult {
+            outcome,
+            edges: self.included_edges(),
+            deductions: self.deductions,
+        }
+    }
+}
+
+impl HintSolver {
+    // the undecided edge at the vertex with the fewest undecided edges
+    fn branch_candidate(&self) -> Option<usize> {
+        let v = (0..self.hints.len())
+            .filter(|v| self.count(*v, EdgeState::Unknown) > 0)
+            .min_by_key(|v| self.count(*v, EdgeState::Unknown))?;
+        self.incident[v]
+            .iter()
+            .find(|c| self.state[**c] == EdgeState::Unknown)
+            .copied()
+    }
+
+    fn search(
+        &mut self,
+        limit: usize,
+        found: &mut Vec<Edges>,
+        nodes: &mut usize,
+    ) -> Result<(), SearchBudgetExceeded> {
+        *nodes += 1;
+        if *nodes > MAX_SEARCH_NODES {
+            return Err(SearchBudgetExceeded);
+        }
+
+        if self.propagate().is_err() {
+            return Ok(());
+        }
+
+        let c = match self.branch_candidate() {
+            Some(c) => c,
+            None => {
+                if self.is_solved() {
+                    found.push(self.included_edges());
+                }
+                return Ok(());
+            }
+        };
+
+        for include in [true, false] {
+            if found.len() >= limit {
+                break;
+            }
+
+            let state = self.state.clone();
+            let n = self.deductions.len();
+            if self.set(c, include, Rule::Assumption).is_ok() {
+                self.search(limit, found, nodes)?;
+            }
+            self.state = state;
+            self.deductions.truncate(n);
+        }
+
+        Ok(())
+    }
+
+    // returns up to `limit` distinct solutions, or None if the search was
+    // too large to finish
+    pub fn find_solutions(mut self, limit: usize) -> Option<Vec<Edges>> {
+        let mut found = Vec::new();
+        let mut nodes = 0;
+        self.search(limit, &mut found, &mut nodes).ok()?;
+        Some(found)
+    }
+}
+
+// whether the hints lead to the authored solution and no other. this can
+// take a while on a large puzzle, so the editor runs it in the background
+pub fn analyze_solutions(hints: Vec<VertexHint>, solution: &Edges) -> SolutionAnalysis {
+    // every vertex is a possible neighbor, so the whole edge space is
+    // searched rather than just the nearby edges the autosolver tries first
+    let config = SolverConfig {
+        neighbors: usize::MAX,
+        triangle_closure: false,
+    };
+
+    let solver = HintSolver::new(hints, config);
+    if !solver.covers(solution) {
+        return SolutionAnalysis::Unknown;
+    }
+
+    let found = match solver.find_solutions(2) {
+        Some(found) => found,
+        None => return SolutionAnalysis::Inconclusive,
+    };
+
+    if found.is_empty() {
+        return SolutionAnalysis::Unsolvable;
+    }
+
+    match found.into_iter().find(|e| e != solution) {
+        Some(alternate) => SolutionAnalysis::Multiple(alternate),
+        None => SolutionAnalysis::Unique,
+    }
+}
+
+// how many vertices are on the convex hull, including any lying along its
+// sides, or None if they're all in a line
+fn hull_size(points: &[Vec2]) -> Option<usize> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+
+    // monotone chain, dropping collinear points
+    let mut hull: Vec<Vec2> = Vec::new();
+    for pass in [sorted.clone(), sorted.iter().rev().copied().collect()] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2 {
+                let (a, b) = (hull[hull.len() - 2], hull[hull.len() - 1]);
+                if (b - a).perp_dot(p - a) > 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+    }
+
+    if hull.len() < 3 {
+        return None;
+    }
+
+    let on_hull = |p: &&Vec2| {
+        (0..hull.len())
+            .any(|i| distance_to_segment(**p, hull[i], hull[(i + 1) % hull.len()]) < 0.01)
+    };
+    Some(points.iter().filter(on_hull).count())
+}
+
+// a triangulation of n points, h of them on the hull, has 3n - 3 - h edges,
+// and no crossing-free set of edges has more. hints asking for exactly that
+// many can only be solved by a triangulation
+fn is_full_triangulation(hints: &[VertexHint]) -> bool {
+    let points: Vec<Vec2> = hints.iter().map(|h| h.pos).collect();
+    let Some(h) = hull_size(&points) else {
+        return false;
+    };
+    let total: usize = hints.iter().map(|h| h.degree).sum();
+    total == 2 * (3 * hints.len() - 3 - h)
+}
+
+// solves with nearby edges first, since that's much quicker; if that isn't
+// enough, the edge the puzzle needs may be a long one, so every edge is tried
+pub fn solve_from_hints(hints: Vec<VertexHint>) -> SolverResult {
+    let config = SolverConfig::default();
+    let result = HintSolver::new(hints.clone(), config).solve();
+    if result.outcome == SolverOutcome::Solved || config.neighbors + 1 >= hints.len() {
+        return result;
+    }
+
+    let config = SolverConfig {
+        neighbors: usize::MAX,
+        ..config
+    };
+    HintSolver::new(hints, config).solve()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hints(points: &[(f32, f32, usize)]) -> Vec<VertexHint> {
+        points
+            .iter()
+            .enumerate()
+            .map(|(id, (x, y, degree))| VertexHint {
+                id,
+                pos: Vec2::new(*x, *y),
+                degree: *degree,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_triangle() {
+        let h = hints(&[(0.0, 0.0, 2), (10.0, 0.0, 2), (0.0, 10.0, 2)]);
+        let result = solve_from_hints(h);
+        assert_eq!(result.outcome, SolverOutcome::Solved);
+        assert_eq!(result.edges.0.len(), 3);
+        assert!(result.deductions.iter().all(|d| d.include));
+    }
+
+    #[test]
+    fn crossing_excludes_diagonal() {
+        // a square with one diagonal; the degree 3 corners force the
+        // diagonal, which rules out the other one.
+        let h = hints(&[
+            (0.0, 0.0, 3),
+            (10.0, 0.0, 2),
+            (10.0, 10.0, 3),
+            (0.0, 10.0, 2),
+        ]);
+        let result = solve_from_hints(h);
+        assert_eq!(result.outcome, SolverOutcome::Solved);
+        assert!(result.edges.is_edge(0, 2));
+        assert!(!result.edges.is_edge(1, 3));
+        assert_eq!(result.edges.0.len(), 5);
+    }
+
+    #[test]
+    fn unique_cycle() {
+        // the only crossing-free way to give every corner two edges
+        let h = hints(&[
+            (0.0, 0.0, 2),
+            (10.0, 0.0, 2),
+            (10.0, 10.0, 2),
+            (0.0, 10.0, 2),
+        ]);
+        let config = SolverConfig {
+            triangle_closure: false,
+            ..default()
+        };
+        let found = HintSolver::new(h, config).find_solutions(2).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0.len(), 4);
+    }
+
+    #[test]
+    fn covers_only_candidates() {
+        // the middle vertex sits on the line between the other two
+        let h = hints(&[(0.0, 0.0, 1), (5.0, 0.0, 2), (10.0, 0.0, 1)]);
+        let solver = HintSolver::new(h, SolverConfig::default());
+        let mut edges = Edges::default();
+        edges.add_edge(0, 1);
+        assert!(solver.covers(&edges));
+        edges.add_edge(0, 2);
+        assert!(!solver.covers(&edges));
+    }
+
+    #[test]
+    fn ambiguous_matching() {
+        // the corners can be paired off horizontally or vertically
+        let h = hints(&[
+            (0.0, 0.0, 1),
+            (10.0, 0.0, 1),
+            (10.0, 10.0, 1),
+            (0.0, 10.0, 1),
+        ]);
+        let config = SolverConfig {
+            triangle_closure: false,
+            ..default()
+        };
+        let found = HintSolver::new(h, config).find_solutions(2).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_ne!(found[0], found[1]);
+    }
+
+    // sets edges as though the player had placed them, then closes what
+    // it can
+    fn close(h: Vec<VertexHint>, edges: &[(usize, usize)]) -> HintSolver {
+        let mut solver = HintSolver::new(h, SolverConfig::default());
+        for (a, b) in edges {
+            let c = solver.lookup[&(*a, *b)];
+            assert!(solver.set(c, true, Rule::Assumption).is_ok());
+        }
+        assert!(solver.apply_triangle_closure().is_ok());
+        solver
+    }
+
+    fn state(solver: &HintSolver, a: usize, b: usize) -> EdgeState {
+        solver.state[solver.lookup[&(a, b)]]
+    }
+
+    #[test]
+    fn closes_full_triangulations() {
+        // 3 sits inside the triangle 0-1-2, and every vertex has 3 edges,
+        // which is all four points can take
+        let h = hints(&[(0.0, 0.0, 3), (10.0, 5.0, 3), (0.0, 10.0, 3), (4.0, 5.0, 3)]);
+        assert!(is_full_triangulation(&h));
+        let solver = close(h, &[(0, 3), (1, 3), (2, 3)]);
+        assert_eq!(state(&solver, 0, 1), EdgeState::In);
+        assert_eq!(state(&solver, 1, 2), EdgeState::In);
+        assert_eq!(state(&solver, 0, 2), EdgeState::In);
+    }
+
+    #[test]
+    fn closure_skips_concave_outline() {
+        // the same points as an arrowhead, with no edge 0-2; the wedge
+        // from 2 round to 0 is outside the puzzle
+        let h = hints(&[(0.0, 0.0, 2), (10.0, 5.0, 3), (0.0, 10.0, 2), (4.0, 5.0, 3)]);
+        assert!(!is_full_triangulation(&h));
+        let solver = close(h, &[(0, 3), (1, 3), (2, 3)]);
+        assert_eq!(state(&solver, 0, 2), EdgeState::Unknown);
+    }
+
+    #[test]
+    fn closure_skips_quad_faces() {
+        let h = hints(&[
+            (0.0, 0.0, 2),
+            (10.0, 0.0, 2),
+            (10.0, 10.0, 2),
+            (0.0, 10.0, 2),
+        ]);
+        let solver = close(h, &[(0, 1), (0, 3)]);
+        assert_eq!(state(&solver, 1, 3), EdgeState::Unknown);
+    }
+
+    #[test]
+    fn closure_waits_for_edges_in_between() {
+        // a fan around 0; 0-2 might still lie between 0-1 and 0-3
+        let h = hints(&[
+            (0.0, 0.0, 3),
+            (10.0, 0.0, 2),
+            (10.0, 10.0, 3),
+            (0.0, 10.0, 2),
+        ]);
+        assert!(is_full_triangulation(&h));
+        let solver = close(h, &[(0, 1), (0, 3)]);
+        assert_eq!(state(&solver, 1, 3), EdgeState::Unknown);
+    }
+
+    #[test]
+    fn falls_back_to_distant_edges() {
+        // a row of vertices which each need an edge to one far above; the
+        // ones at the ends aren't among its ten nearest neighbors
+        let mut points = vec![(0.0, 1000.0, 12)];
+        for i in 0..12 {
+            points.push((i as f32 * 10.0 - 55.0, 0.0, 1));
+        }
+        let h = hints(&points);
+        let config = SolverConfig {
+            triangle_closure: false,
+            ..default()
+        };
+        let nearby = HintSolver::new(h.clone(), config).solve();
+        assert_ne!(nearby.outcome, SolverOutcome::Solved);
+        let result = solve_from_hints(h);
+        assert_eq!(result.outcome, SolverOutcome::Solved);
+        assert!(result.edges.is_edge(0, 1));
+        assert!(result.edges.is_edge(0, 12));
+    }
+
+    #[test]
+    fn crossings_match_every_pair() {
+        // a scatter of points, with one that needs no edges
+        let points: Vec<(f32, f32, usize)> = (0..20)
+            .map(|i| {
+                let (x, y) = ((i * 37 % 23) as f32, (i * 11 % 17) as f32);
+                (x * 3.0, y * 5.0, if i == 7 { 0 } else { 3 })
+            })
+            .collect();
+        let config = SolverConfig {
+            neighbors: usize::MAX,
+            ..default()
+        };
+        let solver = HintSolver::new(hints(&points), config);
+
+        assert!(solver.incident[7].is_empty());
+        for (c1, (i, j)) in solver.candidates.iter().enumerate() {
+            for (c2, (k, l)) in solver.candidates.iter().enumerate() {
+                let h = &solver.hints;
+                let cross = segments_cross(h[*i].pos, h[*j].pos, h[*k].pos, h[*l].pos);
+                assert_eq!(solver.crossings[c1].contains(&c2), cross);
+            }
+        }
+    }
+
+    #[test]
+    fn impossible_degrees() {
+        let h = hints(&[(0.0, 0.0, 3), (10.0, 0.0, 1)]);
+        let result = solve_from_hints(h);
+        assert_eq!(result.outcome, SolverOutcome::Contradiction);
+    }
+}
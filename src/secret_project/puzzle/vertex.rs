@@ -2,8 +2,12 @@ use crate::secret_project::*;
 
 pub struct Vertex {
     pub pos: Vec2,
+    // edges the player has placed which are part of the solution
     pub visible_count: usize,
+    // solution edges the player has yet to place
     pub invisible_count: usize,
+    // edges the player has placed which are not part of the solution
+    pub wrong_count: usize,
 }
 
 impl Vertex {
@@ -12,6 +16,21 @@ impl Vertex {
             pos,
             visible_count: 0,
             invisible_count: 0,
+            wrong_count: 0,
         }
     }
+
+    // one hint dot per edge, in order around the vertex
+    pub fn hint_colors(&self) -> impl Iterator<Item = Srgba> {
+        let remaining = std::iter::repeat_n(BLACK, self.invisible_count);
+        let placed = std::iter::repeat_n(GRAY, self.visible_count);
+        let wrong = std::iter::repeat_n(RED, self.wrong_count);
+        remaining.chain(placed).chain(wrong)
+    }
+}
+
+// where the i-th of n hint dots sits relative to its vertex
+pub fn hint_dot_offset(i: usize, n: usize, radius: f32) -> Vec2 {
+    let a = std::f32::consts::PI * (0.5 + 2.0 * i as f32 / n as f32);
+    Vec2::from_angle(a) * radius
 }
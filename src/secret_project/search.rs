@@ -0,0 +1,197 @@
+use crate::secret_project::*;
+use levenshtein::levenshtein;
+
+// how far off a word can be and still count as a match, so that short
+// queries don't match everything
+fn max_typos(query: &str) -> usize {
+    match query.chars().count() {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+// how closely the query matches some part of the text; lower is better.
+// the query is compared against the start of the text and of each word in
+// it, so partially typed words still match
+fn match_score(query: &str, text: &str) -> Option<usize> {
+    let text = text.to_lowercase();
+    if text.starts_with(query) {
+        return Some(0);
+    }
+    if text.contains(query) {
+        return Some(1);
+    }
+
+    let n = query.chars().count();
+    let prefix = |s: &str| s.chars().take(n).collect::<String>();
+
+    std::iter::once(text.as_str())
+        .chain(text.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(|word| levenshtein(query, &prefix(word)))
+        .filter(|d| *d <= max_typos(query))
+        .min()
+        .map(|d| d + 2)
+}
+
+// a word like tag:animals in the query, which narrows down the results
+// rather than being searched for
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Tag(String),
+    Author(String),
+    Difficulty(u8),
+}
+
+impl Filter {
+    fn parse(word: &str) -> Option<Self> {
+        let (key, value) = word.split_once(':')?;
+        if value.is_empty() {
+            return None;
+        }
+        match key {
+            "tag" => Some(Filter::Tag(value.to_string())),
+            "by" | "author" => Some(Filter::Author(value.to_string())),
+            "difficulty" => value.parse().ok().map(Filter::Difficulty),
+            _ => None,
+        }
+    }
+
+    fn matches(&self, info: &PuzzleManifestInfo) -> bool {
+        match self {
+            Filter::Tag(tag) => info.tags.iter().any(|t| t.to_lowercase().starts_with(tag)),
+            Filter::Author(author) => info
+                .author
+                .as_ref()
+                .is_some_and(|a| a.to_lowercase().contains(author)),
+            Filter::Difficulty(d) => info.difficulty == Some(*d),
+        }
+    }
+}
+
+// the best score of the query against any of the puzzle's text; the title
+// and short name count for more than the other metadata
+fn info_score(query: &str, info: &PuzzleManifestInfo) -> Option<usize> {
+    let names = [&info.title, &info.short_name]
+        .into_iter()
+        .filter_map(|s| match_score(query, s));
+    let metadata = info
+        .author
+        .iter()
+        .chain(&info.tags)
+        .filter_map(|s| match_score(query, s))
+        .map(|score| score + 1);
+    let description = info
+        .description
+        .as_ref()
+        .filter(|d| d.to_lowercase().contains(query))
+        .map(|_| 4);
+    names.chain(metadata).chain(description).min()
+}
+
+// indices of the manifest entries matching the query, best first. an empty
+// query matches everything, in manifest order
+pub fn search_manifest(manifest: &Manifest, query: &str) -> Vec<usize> {
+    let query = query.trim().to_lowercase();
+
+    let mut filters = Vec::new();
+    let mut words = Vec::new();
+    for word in query.split_whitespace() {
+        match Filter::parse(word) {
+            Some(filter) => filters.push(filter),
+            None => words.push(word),
+        }
+    }
+    let query = words.join(" ");
+
+    let candidates = manifest
+        .puzzles
+        .iter()
+        .enumerate()
+        .filter(|(_, info)| filters.iter().all(|f| f.matches(info)));
+
+    if query.is_empty() {
+        return candidates.map(|(id, _)| id).collect();
+    }
+
+    let mut scored: Vec<(usize, usize)> = candidates
+        .filter_map(|(id, info)| Some((info_score(&query, info)?, id)))
+        .collect();
+
+    scored.sort();
+    scored.into_iter().map(|(_, id)| id).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> Manifest {
+        Manifest {
+            puzzles: vec![
+                PuzzleManifestInfo {
+                    author: Some("Jane Doe".to_string()),
+                    tags: vec!["flowers".to_string()],
+                    difficulty: Some(2),
+                    ..PuzzleManifestInfo::new("rose", "A Rose by Any Other Name")
+                },
+                PuzzleManifestInfo {
+                    description: Some("A stormy night on the coast".to_string()),
+                    difficulty: Some(4),
+                    ..PuzzleManifestInfo::new("lighthouse", "The Lighthouse")
+                },
+                PuzzleManifestInfo {
+                    author: Some("Sam Smith".to_string()),
+                    tags: vec!["animals".to_string()],
+                    difficulty: Some(2),
+                    ..PuzzleManifestInfo::new("fox", "Red Fox")
+                },
+                PuzzleManifestInfo {
+                    tags: vec!["flowers".to_string()],
+                    ..PuzzleManifestInfo::new("roses2", "More Roses")
+                },
+            ],
+            ..default()
+        }
+    }
+
+    #[test]
+    fn empty_query_lists_everything() {
+        assert_eq!(search_manifest(&manifest(), "  "), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn exact_matches_rank_first() {
+        assert_eq!(search_manifest(&manifest(), "rose"), vec![0, 3]);
+        assert_eq!(search_manifest(&manifest(), "FOX"), vec![2]);
+    }
+
+    #[test]
+    fn partial_words_match() {
+        assert_eq!(search_manifest(&manifest(), "light"), vec![1]);
+    }
+
+    #[test]
+    fn typos_are_forgiven() {
+        assert_eq!(search_manifest(&manifest(), "lihgthouse"), vec![1]);
+        assert_eq!(search_manifest(&manifest(), "foz"), vec![2]);
+        assert!(search_manifest(&manifest(), "xyz").is_empty());
+    }
+
+    #[test]
+    fn searches_metadata() {
+        assert_eq!(search_manifest(&manifest(), "smith"), vec![2]);
+        assert_eq!(search_manifest(&manifest(), "animals"), vec![2]);
+        assert_eq!(search_manifest(&manifest(), "stormy"), vec![1]);
+    }
+
+    #[test]
+    fn filters_narrow_results() {
+        assert_eq!(search_manifest(&manifest(), "tag:flowers"), vec![0, 3]);
+        assert_eq!(search_manifest(&manifest(), "difficulty:2"), vec![0, 2]);
+        assert_eq!(search_manifest(&manifest(), "by:jane"), vec![0]);
+        assert_eq!(search_manifest(&manifest(), "difficulty:2 fox"), vec![2]);
+        assert!(search_manifest(&manifest(), "tag:flowers fox").is_empty());
+    }
+}
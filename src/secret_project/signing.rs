@@ -0,0 +1,184 @@
+use crate::secret_project::*;
+use ring::signature::{UnparsedPublicKey, ED25519};
+
+// hex ed25519 public key of whoever publishes the default puzzle source.
+// the private half is kept offline and used to sign manifest.yaml
+pub const DEFAULT_MANIFEST_KEY: &'static str =
+    "001d87bed604b730b258787f844d8f1bee4af97cc98c40540a7d25699f99f7b9";
+
+pub const MANIFEST_SIGNATURE_PATH: &'static str = "manifest.yaml.sig";
+pub const MAX_SIGNATURE_SIZE: u64 = 1024;
+
+// the detached signature for a manifest lives next to it
+pub fn signature_file(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".sig");
+    path.with_file_name(name)
+}
+
+pub fn from_hex(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim();
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// decides whether a manifest can be installed
+#[derive(Debug, Clone)]
+pub struct ManifestVerifier {
+    key: Option<Vec<u8>>,
+    trusted: bool,
+}
+
+impl ManifestVerifier {
+    // a key which isn't valid hex rejects every signature
+    pub fn new(key_hex: &str) -> Self {
+        Self {
+            key: from_hex(key_hex),
+            trusted: false,
+        }
+    }
+
+    // accepts manifests whether or not they're signed
+    pub fn trusted() -> Self {
+        Self {
+            key: None,
+            trusted: true,
+        }
+    }
+
+    // the key from the settings, or the built in one; the configured
+    // source is trusted only if the user has said so
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self::for_source(settings, &settings.puzzle_source)
+    }
+
+    pub fn for_source(settings: &Settings, source: &str) -> Self {
+        let key = settings
+            .manifest_key
+            .as_deref()
+            .unwrap_or(DEFAULT_MANIFEST_KEY);
+        Self {
+            trusted: settings.trusted_sources.iter().any(|s| s == source),
+            ..Self::new(key)
+        }
+    }
+
+    pub fn verify(
+        &self,
+        file: &str,
+        contents: &[u8],
+        signature_hex: Option<&str>,
+    ) -> Result<(), VertexError> {
+        if self.trusted {
+            return Ok(());
+        }
+
+        let Some(signature_hex) = signature_hex else {
+            return Err(VertexError::Integrity(IntegrityError::Unsigned {
+                file: file.to_string(),
+            }));
+        };
+
+        let bad_signature = || {
+            VertexError::Integrity(IntegrityError::BadSignature {
+                file: file.to_string(),
+            })
+        };
+
+        let key = self.key.as_ref().ok_or_else(bad_signature)?;
+        let signature = from_hex(signature_hex).ok_or_else(bad_signature)?;
+        UnparsedPublicKey::new(&ED25519, key)
+            .verify(contents, &signature)
+            .map_err(|_| bad_signature())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn keypair() -> Ed25519KeyPair {
+        Ed25519KeyPair::from_seed_unchecked(&[7; 32]).unwrap()
+    }
+
+    fn verifier() -> ManifestVerifier {
+        ManifestVerifier::new(&to_hex(keypair().public_key().as_ref()))
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        assert_eq!(from_hex("00ff10"), Some(vec![0, 255, 16]));
+        assert_eq!(from_hex("0"), None);
+        assert_eq!(from_hex("zz"), None);
+    }
+
+    #[test]
+    fn accepts_good_signatures() {
+        let contents = b"puzzles: []";
+        let signature = to_hex(keypair().sign(contents).as_ref());
+        assert!(verifier()
+            .verify("manifest", contents, Some(&signature))
+            .is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_or_missing_signatures() {
+        let signature = to_hex(keypair().sign(b"puzzles: []").as_ref());
+        assert!(matches!(
+            verifier().verify("manifest", b"puzzles: [evil]", Some(&signature)),
+            Err(VertexError::Integrity(IntegrityError::BadSignature { .. }))
+        ));
+        assert!(matches!(
+            verifier().verify("manifest", b"puzzles: []", None),
+            Err(VertexError::Integrity(IntegrityError::Unsigned { .. }))
+        ));
+        assert!(ManifestVerifier::trusted()
+            .verify("manifest", b"puzzles: []", None)
+            .is_ok());
+    }
+
+    #[test]
+    fn built_in_key_by_default() {
+        let contents = b"puzzles: []";
+        let signature = to_hex(keypair().sign(contents).as_ref());
+
+        let stock = ManifestVerifier::from_settings(&Settings::default());
+        assert!(stock.verify("manifest", contents, None).is_err());
+        assert!(stock
+            .verify("manifest", contents, Some(&signature))
+            .is_err());
+
+        let settings = Settings {
+            manifest_key: Some(to_hex(keypair().public_key().as_ref())),
+            ..default()
+        };
+        let custom = ManifestVerifier::from_settings(&settings);
+        assert!(custom
+            .verify("manifest", contents, Some(&signature))
+            .is_ok());
+        assert!(custom.verify("manifest", contents, None).is_err());
+    }
+
+    #[test]
+    fn trust_is_per_source() {
+        let settings = Settings {
+            manifest_key: Some(to_hex(keypair().public_key().as_ref())),
+            trusted_sources: vec!["/home/me/puzzles".to_string()],
+            ..default()
+        };
+        let trusted = ManifestVerifier::for_source(&settings, "/home/me/puzzles");
+        assert!(trusted.verify("manifest", b"", None).is_ok());
+        let untrusted = ManifestVerifier::for_source(&settings, "https://example.com");
+        assert!(untrusted.verify("manifest", b"", None).is_err());
+    }
+}
@@ -57,13 +57,14 @@ fn add_new_sounds(
     mut commands: Commands,
     mut sounds: MessageReader<SoundEffect>,
     asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
 ) {
     for sound in sounds.read() {
         debug!("{:?}", sound);
         commands.spawn((
             Name::new(format!("{:?}", sound)),
             AudioPlayer::new(asset_server.load(sound.to_path())),
-            PlaybackSettings::DESPAWN.with_volume(Volume::Linear(0.2)),
+            PlaybackSettings::DESPAWN.with_volume(Volume::Linear(0.2 * settings.volume)),
         ));
     }
 }
@@ -0,0 +1,361 @@
+use crate::secret_project::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub const DEFAULT_PUZZLE_SOURCE: &'static str = "https://jwade109.github.io/vertex_puzzles";
+
+// paths within a source, which is laid out like the puzzle website
+pub const MANIFEST_PATH: &'static str = "manifest.yaml";
+
+pub fn puzzle_file_path(short_name: &str) -> String {
+    format!("{}/puzzle.txt", short_name)
+}
+
+// generous compared to any real puzzle, but stops a broken or hostile
+// server from filling the disk
+pub const MAX_PUZZLE_SIZE: u64 = 4 * 1024 * 1024;
+pub const MAX_MANIFEST_SIZE: u64 = 1024 * 1024;
+
+// somewhere puzzles and their manifest can be fetched from
+pub trait PuzzleSource: Send + Sync {
+    fn describe(&self) -> String;
+
+    // writes the file at the given path within the source to dest,
+    // replacing anything already there. fails without writing more than
+    // limit bytes if the file is any larger. bytes are added to received as
+    // they arrive
+    fn fetch(
+        &self,
+        path: &str,
+        dest: &Path,
+        limit: u64,
+        received: &AtomicU64,
+    ) -> Result<u64, VertexError>;
+
+    // called when a fetched file turns out to be bad, so that anything kept
+    // about it is dropped and the next fetch starts from scratch
+    fn forget(&self, _path: &str) {}
+
+    // the same kind of source, rooted at a path within this one
+    fn join(&self, path: &str) -> Box<dyn PuzzleSource>;
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpSource {
+    base: String,
+    cache: Option<FetchCache>,
+}
+
+impl HttpSource {
+    pub fn new(base: impl Into<String>) -> Self {
+        Self {
+            base: base.into(),
+            cache: None,
+        }
+    }
+
+    // files which haven't changed since they were cached are copied from
+    // the cache rather than downloaded again
+    pub fn with_cache(self, cache: FetchCache) -> Self {
+        Self {
+            cache: Some(cache),
+            ..self
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base.trim_end_matches('/'), path)
+    }
+}
+
+impl PuzzleSource for HttpSource {
+    fn describe(&self) -> String {
+        self.base.clone()
+    }
+
+    fn fetch(
+        &self,
+        path: &str,
+        dest: &Path,
+        limit: u64,
+        received: &AtomicU64,
+    ) -> Result<u64, VertexError> {
+        let url = self.url(path);
+        let Some(cache) = &self.cache else {
+            return download_file(&url, dest, true, limit, received);
+        };
+
+        let validators = cache.validators(&url);
+        match download_if_changed(&url, dest, limit, validators.as_ref(), received)? {
+            Download::NotModified => {
+                info!("{} is up to date", url);
+                let bytes = cache.restore(&url, dest)?;
+                received.fetch_add(bytes, Ordering::Relaxed);
+                Ok(bytes)
+            }
+            Download::Fetched { bytes, validators } => {
+                if let Err(e) = cache.store(&url, dest, &validators) {
+                    warn!("Failed to cache {}: {:?}", url, e);
+                }
+                Ok(bytes)
+            }
+        }
+    }
+
+    fn forget(&self, path: &str) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        if let Err(e) = cache.remove(&self.url(path)) {
+            warn!("Failed to drop {} from the cache: {:?}", path, e);
+        }
+    }
+
+    fn join(&self, path: &str) -> Box<dyn PuzzleSource> {
+        Box::new(Self {
+            base: self.url(path),
+            cache: self.cache.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DirectorySource {
+    root: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl PuzzleSource for DirectorySource {
+    fn describe(&self) -> String {
+        format!("file://{}", self.root.display())
+    }
+
+    fn fetch(
+        &self,
+        path: &str,
+        dest: &Path,
+        limit: u64,
+        received: &AtomicU64,
+    ) -> Result<u64, VertexError> {
+        let src = self.root.join(path);
+        let size = std::fs::metadata(&src)?.len();
+        if size > limit {
+            return Err(IntegrityError::too_large(path, limit));
+        }
+        info!("Copying {} to {}", src.display(), dest.display());
+        let bytes = std::fs::copy(src, dest)?;
+        received.fetch_add(bytes, Ordering::Relaxed);
+        Ok(bytes)
+    }
+
+    fn join(&self, path: &str) -> Box<dyn PuzzleSource> {
+        Box::new(Self::new(self.root.join(path)))
+    }
+}
+
+// a source held entirely in memory, for tests
+#[derive(Debug, Clone, Default)]
+pub struct MemorySource {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl MemorySource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, path: impl Into<String>, contents: impl Into<Vec<u8>>) {
+        self.files.insert(path.into(), contents.into());
+    }
+
+    pub fn remove(&mut self, path: &str) {
+        self.files.remove(path);
+    }
+}
+
+impl PuzzleSource for MemorySource {
+    fn describe(&self) -> String {
+        format!("memory ({} files)", self.files.len())
+    }
+
+    fn fetch(
+        &self,
+        path: &str,
+        dest: &Path,
+        limit: u64,
+        received: &AtomicU64,
+    ) -> Result<u64, VertexError> {
+        let contents = self
+            .files
+            .get(path)
+            .ok_or(VertexError::Str(format!("No file \"{}\" in source", path)))?;
+        if contents.len() as u64 > limit {
+            return Err(IntegrityError::too_large(path, limit));
+        }
+        std::fs::write(dest, contents)?;
+        received.fetch_add(contents.len() as u64, Ordering::Relaxed);
+        Ok(contents.len() as u64)
+    }
+
+    fn join(&self, path: &str) -> Box<dyn PuzzleSource> {
+        let prefix = format!("{}/", path.trim_end_matches('/'));
+        let files = self
+            .files
+            .iter()
+            .filter_map(|(p, contents)| {
+                let p = p.strip_prefix(&prefix)?;
+                Some((p.to_string(), contents.clone()))
+            })
+            .collect();
+        Box::new(Self { files })
+    }
+}
+
+// a source as written in the settings file or on the command line; either
+// an http(s) base url, or a local directory with or without file://
+pub fn parse_source(s: &str, cache: Option<FetchCache>) -> Box<dyn PuzzleSource> {
+    if s.starts_with("http://") || s.starts_with("https://") {
+        let source = HttpSource::new(s);
+        match cache {
+            Some(cache) => Box::new(source.with_cache(cache)),
+            None => Box::new(source),
+        }
+    } else if let Some(path) = s.strip_prefix("file://") {
+        Box::new(DirectorySource::new(path))
+    } else {
+        Box::new(DirectorySource::new(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn parses_sources() {
+        assert_eq!(
+            parse_source("https://example.com/puzzles/", None).describe(),
+            "https://example.com/puzzles/"
+        );
+        assert_eq!(
+            parse_source("file:///tmp/p", None).describe(),
+            "file:///tmp/p"
+        );
+        assert_eq!(parse_source("/tmp/p", None).describe(), "file:///tmp/p");
+    }
+
+    #[test]
+    fn urls_join_paths() {
+        let source = HttpSource::new("https://example.com/puzzles/");
+        assert_eq!(
+            source.url(&puzzle_file_path("rose")),
+            "https://example.com/puzzles/rose/puzzle.txt"
+        );
+    }
+
+    // answers each connection with the file, or 304 if the request already
+    // has its etag, and counts the full responses. the first `dropped`
+    // responses hang up halfway through the body
+    fn serve(body: &'static str, connections: usize, dropped: usize) -> (String, Arc<AtomicUsize>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let full = Arc::new(AtomicUsize::new(0));
+        let counter = full.clone();
+
+        std::thread::spawn(move || {
+            for (i, stream) in listener.incoming().take(connections).enumerate() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut conditional = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    let line = line.to_lowercase();
+                    conditional |= line.starts_with("if-none-match: \"v1\"");
+                }
+                let response = match conditional {
+                    true => "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string(),
+                    false => {
+                        let sent = match i < dropped {
+                            true => &body[..body.len() / 2],
+                            false => {
+                                counter.fetch_add(1, Ordering::Relaxed);
+                                body
+                            }
+                        };
+                        format!(
+                            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            body.len(),
+                            sent
+                        )
+                    }
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (base, full)
+    }
+
+    #[test]
+    fn unchanged_files_come_from_the_cache() {
+        let root = std::env::temp_dir().join("vertex_fetch_cache_test");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        let (base, full) = serve("puzzles: []", 5, 0);
+        let source = HttpSource::new(base).with_cache(FetchCache::new(root.join("cache")));
+        let dest = root.join("manifest.yaml");
+
+        for _ in 0..3 {
+            let _ = std::fs::remove_file(&dest);
+            assert_eq!(
+                source
+                    .fetch(MANIFEST_PATH, &dest, 1024, &AtomicU64::default())
+                    .unwrap(),
+                11
+            );
+            assert_eq!(std::fs::read_to_string(&dest).unwrap(), "puzzles: []");
+        }
+        assert_eq!(full.load(Ordering::Relaxed), 1);
+
+        // a forgotten file is downloaded again, then cached again
+        source.forget(MANIFEST_PATH);
+        for _ in 0..2 {
+            source
+                .fetch(MANIFEST_PATH, &dest, 1024, &AtomicU64::default())
+                .unwrap();
+        }
+        assert_eq!(full.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn dropped_bodies_are_retried_and_counted_once() {
+        let root = std::env::temp_dir().join("vertex_dropped_body_test");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+
+        let (base, full) = serve("puzzles: []", 2, 1);
+        let source = HttpSource::new(base);
+        let dest = root.join("manifest.yaml");
+        let received = AtomicU64::default();
+
+        assert_eq!(
+            source.fetch(MANIFEST_PATH, &dest, 1024, &received).unwrap(),
+            11
+        );
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "puzzles: []");
+        assert_eq!(received.load(Ordering::Relaxed), 11);
+        assert_eq!(full.load(Ordering::Relaxed), 1);
+    }
+}
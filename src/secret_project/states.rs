@@ -11,6 +11,11 @@ pub enum AppState {
     Editing {
         mode: EditorMode,
     },
+    // the pause menu, over whichever of the editor or play mode it was
+    // opened from
+    Paused {
+        editing: Option<EditorMode>,
+    },
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
@@ -20,6 +25,7 @@ pub enum EditorMode {
     Images,
     Select,
     Eraser,
+    Move,
 }
 
 impl AppState {
@@ -43,6 +49,44 @@ impl AppState {
             _ => false,
         }
     }
+
+    pub fn is_paused(&self) -> bool {
+        match self {
+            Self::Paused { .. } => true,
+            _ => false,
+        }
+    }
+
+    // whether the player's edges are what's on screen, and so worth saving
+    pub fn has_game_progress(&self) -> bool {
+        match self {
+            Self::Playing { .. } => true,
+            Self::Paused { editing: None } => true,
+            _ => false,
+        }
+    }
+
+    // the paused version of this state, if it can be paused at all
+    pub fn paused(&self) -> Option<AppState> {
+        match *self {
+            Self::Playing { victory: false } => Some(Self::Paused { editing: None }),
+            Self::Editing { mode } => Some(Self::Paused {
+                editing: Some(mode),
+            }),
+            _ => None,
+        }
+    }
+
+    // the state to go back to when leaving the pause menu
+    pub fn resumed(&self) -> AppState {
+        match *self {
+            Self::Paused {
+                editing: Some(mode),
+            } => Self::Editing { mode },
+            Self::Paused { editing: None } => Self::Playing { victory: false },
+            state => state,
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
@@ -55,6 +99,7 @@ impl ComputedStates for InEditorOrPlaying {
         match sources {
             AppState::Playing { .. } => Some(Self),
             AppState::Editing { .. } => Some(Self),
+            AppState::Paused { .. } => Some(Self),
             _ => None,
         }
     }
@@ -74,6 +119,17 @@ impl ComputedStates for VictoryScreen {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct PauseScreen;
+
+impl ComputedStates for PauseScreen {
+    type SourceStates = AppState;
+
+    fn compute(sources: Self::SourceStates) -> Option<Self> {
+        sources.is_paused().then(|| Self)
+    }
+}
+
 pub fn log_app_state_transitions(mut tr: MessageReader<StateTransitionEvent<AppState>>) {
     for msg in tr.read() {
         info!(
@@ -113,6 +169,15 @@ pub fn is_playing(state: Res<State<AppState>>) -> bool {
     state.is_playing()
 }
 
+pub fn uses_selection(state: Res<State<AppState>>) -> bool {
+    match **state {
+        AppState::Editing {
+            mode: EditorMode::Select | EditorMode::Move,
+        } => true,
+        _ => false,
+    }
+}
+
 pub fn is_editor_or_playing(state: Res<State<AppState>>) -> bool {
     state.is_editor() || state.is_playing()
 }
@@ -0,0 +1,230 @@
+use crate::secret_project::*;
+use std::fmt::Write;
+
+pub struct SvgExportPlugin;
+
+impl Plugin for SvgExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<ExportSvg>()
+            .add_systems(Update, export_svg_system);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvgMode {
+    // the finished picture
+    Solution,
+    // just the dots and their degree hints, for solving on paper
+    Blank,
+    // the player's edges and whatever faces they've revealed so far
+    Progress,
+}
+
+impl SvgMode {
+    fn name(&self) -> &'static str {
+        match self {
+            SvgMode::Solution => "solution",
+            SvgMode::Blank => "blank",
+            SvgMode::Progress => "progress",
+        }
+    }
+}
+
+#[derive(Message, Debug)]
+pub struct ExportSvg(pub SvgMode);
+
+const SVG_PADDING: f32 = 40.0;
+const HINT_RADIUS: f32 = 20.0;
+const DOT_RADIUS: f32 = 4.0;
+
+fn hex(color: Srgba) -> String {
+    let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        byte(color.red),
+        byte(color.green),
+        byte(color.blue)
+    )
+}
+
+// svg is y-down, the world is y-up
+fn pt(p: Vec2) -> String {
+    format!("{:.2},{:.2}", p.x, -p.y)
+}
+
+pub fn puzzle_to_svg(puzzle: &Puzzle, save: &SaveData, mode: SvgMode) -> String {
+    let mut min = Vec2::splat(f32::INFINITY);
+    let mut max = Vec2::splat(f32::NEG_INFINITY);
+    for (_, v) in puzzle.vertices() {
+        min = min.min(v.pos);
+        max = max.max(v.pos);
+    }
+    if min.x > max.x {
+        min = Vec2::ZERO;
+        max = Vec2::ZERO;
+    }
+    min -= SVG_PADDING;
+    max += SVG_PADDING;
+
+    let size = max - min;
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{:.2} {:.2} {:.2} {:.2}" width="{:.0}" height="{:.0}">"#,
+        min.x, -max.y, size.x, size.y, size.x, size.y
+    );
+    let _ = writeln!(
+        out,
+        r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="white"/>"#,
+        min.x, -max.y, size.x, size.y
+    );
+
+    // faces, the same ones generate_mesh fills in
+    if mode != SvgMode::Blank {
+        let _ = writeln!(out, r#"<g stroke-linejoin="round">"#);
+        for (points, color) in puzzle.faces(save, mode == SvgMode::Progress) {
+            let points: Vec<String> = points.into_iter().map(pt).collect();
+            let c = hex(color);
+            let _ = writeln!(
+                out,
+                r#"<polygon points="{}" fill="{}" stroke="{}" stroke-width="0.5"/>"#,
+                points.join(" "),
+                c,
+                c
+            );
+        }
+        let _ = writeln!(out, "</g>");
+    }
+
+    if mode == SvgMode::Progress && !puzzle.is_complete(save) {
+        let _ = writeln!(
+            out,
+            r#"<g stroke="black" stroke-width="3" stroke-linecap="round">"#
+        );
+        for (a, b) in puzzle.game_edges(save) {
+            let (a, b) = (a.pos, b.pos);
+            let _ = writeln!(
+                out,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}"/>"#,
+                a.x, -a.y, b.x, -b.y
+            );
+        }
+        let _ = writeln!(out, "</g>");
+    }
+
+    // vertices and hint dots, laid out like draw_vertices
+    if mode != SvgMode::Solution {
+        let _ = writeln!(out, "<g>");
+        for (_, v) in puzzle.vertices() {
+            let (p, q) = (v.pos.x, -v.pos.y);
+            let _ = writeln!(
+                out,
+                r#"<circle cx="{:.2}" cy="{:.2}" r="{}" fill="black"/>"#,
+                p, q, DOT_RADIUS
+            );
+
+            // a blank puzzle shows every hint as still to be placed
+            let colors: Vec<Srgba> = match mode {
+                SvgMode::Blank => vec![BLACK; v.invisible_count + v.visible_count],
+                _ => v.hint_colors().collect(),
+            };
+
+            for (i, color) in colors.iter().enumerate() {
+                let d = v.pos + hint_dot_offset(i, colors.len(), HINT_RADIUS);
+                let _ = writeln!(
+                    out,
+                    r#"<circle cx="{:.2}" cy="{:.2}" r="{}" fill="{}"/>"#,
+                    d.x,
+                    -d.y,
+                    DOT_RADIUS,
+                    hex(*color)
+                );
+            }
+        }
+        let _ = writeln!(out, "</g>");
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+fn export_svg_system(
+    mut commands: Commands,
+    mut messages: MessageReader<ExportSvg>,
+    puzzle: Single<&Puzzle>,
+    save: Res<SaveData>,
+    current: Res<CurrentPuzzle>,
+    install: Res<Installation>,
+) {
+    for msg in messages.read() {
+        let name = current
+            .0
+            .as_ref()
+            .map(|key| key.to_string().replace('/', "-"))
+            .unwrap_or("untitled".to_string());
+
+        let path = install
+            .exports()
+            .join(format!("{}-{}.svg", name, msg.0.name()));
+
+        let svg = puzzle_to_svg(&puzzle, &save, msg.0);
+
+        let result =
+            std::fs::create_dir_all(install.exports()).and_then(|_| std::fs::write(&path, svg));
+
+        let text = match result {
+            Ok(()) => format!("Exported \"{}\"", path.display()),
+            Err(e) => format!("Failed to export \"{}\": {:?}", path.display(), e),
+        };
+        commands.write_message(TextMessage::info(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> (Puzzle, SaveData) {
+        let mut puzzle = Puzzle::default();
+        let ids: Vec<usize> = [(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)]
+            .iter()
+            .map(|(x, y)| puzzle.add_point(Vec2::new(*x, *y)))
+            .collect();
+        for (a, b) in [(0, 1), (1, 2), (2, 3), (3, 0), (0, 2)] {
+            puzzle.add_solution_edge(ids[a], ids[b]);
+        }
+
+        let mut save = SaveData::default();
+        save.edges.add_edge(ids[0], ids[1]);
+        save.edges.add_edge(ids[1], ids[2]);
+        save.edges.add_edge(ids[0], ids[2]);
+        puzzle.update_hints(&save);
+
+        (puzzle, save)
+    }
+
+    #[test]
+    fn solution_has_every_face() {
+        let (puzzle, save) = square();
+        let svg = puzzle_to_svg(&puzzle, &save, SvgMode::Solution);
+        assert_eq!(svg.matches("<polygon").count(), 2);
+        assert_eq!(svg.matches("<circle").count(), 0);
+    }
+
+    #[test]
+    fn blank_has_dots_and_hints() {
+        let (puzzle, save) = square();
+        let svg = puzzle_to_svg(&puzzle, &save, SvgMode::Blank);
+        assert_eq!(svg.matches("<polygon").count(), 0);
+        // four vertices, plus one hint per edge end
+        assert_eq!(svg.matches("<circle").count(), 4 + 2 * 5);
+    }
+
+    #[test]
+    fn progress_reveals_finished_faces() {
+        let (puzzle, save) = square();
+        let svg = puzzle_to_svg(&puzzle, &save, SvgMode::Progress);
+        assert_eq!(svg.matches("<polygon").count(), 1);
+        assert_eq!(svg.matches("<line").count(), 3);
+    }
+}
@@ -0,0 +1,893 @@
+use crate::secret_project::*;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// how many puzzles are fetched at once
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, bytes);
+    digest
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+pub fn file_sha256(path: &Path) -> Result<String, VertexError> {
+    Ok(sha256_hex(&std::fs::read(path)?))
+}
+
+// the hash of an installed file, or None if there isn't one
+fn local_hash(path: &Path) -> Result<Option<String>, VertexError> {
+    match std::fs::exists(path)? {
+        true => Ok(Some(file_sha256(path)?)),
+        false => Ok(None),
+    }
+}
+
+// what a sync needs to do with one puzzle listed in the new manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncStep {
+    Add,
+    Update,
+    Keep,
+}
+
+// old is the entry from the manifest the installed file came from, if any.
+// a hash in the new manifest is compared against the file itself; without
+// one, only a version bump counts as a change
+pub fn sync_step(
+    old: Option<&PuzzleManifestInfo>,
+    new: &PuzzleManifestInfo,
+    local_hash: Option<&str>,
+) -> SyncStep {
+    let Some(local_hash) = local_hash else {
+        return SyncStep::Add;
+    };
+
+    if let Some(hash) = &new.hash {
+        return match hash.eq_ignore_ascii_case(local_hash) {
+            true => SyncStep::Keep,
+            false => SyncStep::Update,
+        };
+    }
+
+    match old {
+        Some(old) if old.version == new.version => SyncStep::Keep,
+        _ => SyncStep::Update,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: usize,
+    pub failed: Vec<(String, VertexError)>,
+}
+
+impl SyncReport {
+    pub fn has_changes(&self) -> bool {
+        !self.added.is_empty() || !self.updated.is_empty() || !self.removed.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        let mut s = format!(
+            "{} added, {} updated, {} removed, {} unchanged",
+            self.added.len(),
+            self.updated.len(),
+            self.removed.len(),
+            self.unchanged
+        );
+        if !self.failed.is_empty() {
+            s += &format!(", {} failed", self.failed.len());
+        }
+        s
+    }
+
+    // adds a pack's report to this one, naming its puzzles by key
+    pub fn merge(&mut self, pack: &str, other: SyncReport) {
+        let key = |short_name: String| PuzzleKey::new(pack, short_name).to_string();
+        self.added.extend(other.added.into_iter().map(key));
+        self.updated.extend(other.updated.into_iter().map(key));
+        self.removed.extend(other.removed.into_iter().map(key));
+        self.unchanged += other.unchanged;
+        self.failed
+            .extend(other.failed.into_iter().map(|(n, e)| (key(n), e)));
+    }
+
+    // which puzzles didn't install and why, if any
+    pub fn failure_summary(&self) -> Option<String> {
+        if self.failed.is_empty() {
+            return None;
+        }
+        let reasons: Vec<String> = self
+            .failed
+            .iter()
+            .map(|(name, e)| format!("{} ({})", name, e))
+            .collect();
+        Some(format!(
+            "Failed to install {}; try syncing again later",
+            reasons.join(", ")
+        ))
+    }
+}
+
+// shared with whoever started a sync, so it can show how far along it is
+#[derive(Debug, Default)]
+pub struct SyncProgress {
+    puzzles_done: AtomicUsize,
+    puzzles_total: AtomicUsize,
+    bytes: AtomicU64,
+}
+
+impl SyncProgress {
+    pub fn puzzles_done(&self) -> usize {
+        self.puzzles_done.load(Ordering::Relaxed)
+    }
+
+    pub fn puzzles_total(&self) -> usize {
+        self.puzzles_total.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn describe(&self) -> String {
+        let total = self.puzzles_total();
+        if total == 0 {
+            return "Fetching manifest".to_string();
+        }
+        format!(
+            "{} of {} puzzles, {:.1} KB",
+            self.puzzles_done(),
+            total,
+            self.bytes() as f64 / 1024.0
+        )
+    }
+}
+
+fn partial_file(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+pub fn check_hash(file: &str, path: &Path, expected: &str) -> Result<(), VertexError> {
+    let found = file_sha256(path)?;
+    if !expected.eq_ignore_ascii_case(&found) {
+        return Err(VertexError::Integrity(IntegrityError::HashMismatch {
+            file: file.to_string(),
+            expected: expected.to_string(),
+            found,
+        }));
+    }
+    Ok(())
+}
+
+// fetches a file next to dest, which is only replaced once the new file is
+// within the size limit, matches the hash if there is one, and passes
+// validate. dest is left alone if anything goes wrong
+pub fn fetch_verified(
+    source: &dyn PuzzleSource,
+    file: &str,
+    dest: &Path,
+    hash: Option<&str>,
+    limit: u64,
+    received: &AtomicU64,
+    validate: impl FnOnce(&Path) -> Result<(), VertexError>,
+) -> Result<u64, VertexError> {
+    let part = partial_file(dest);
+
+    let verified = source
+        .fetch(file, &part, limit, received)
+        .and_then(|bytes| {
+            let checked = match hash {
+                Some(expected) => check_hash(file, &part, expected),
+                None => Ok(()),
+            };
+            if let Err(e) = checked.and_then(|_| validate(&part)) {
+                // it may have come from a cache, which mustn't offer it again
+                source.forget(file);
+                return Err(e);
+            }
+            Ok(bytes)
+        });
+
+    match verified {
+        Ok(bytes) => {
+            std::fs::rename(&part, dest)?;
+            Ok(bytes)
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&part);
+            Err(e)
+        }
+    }
+}
+
+// fetches a puzzle, replacing the installed copy only if the new file
+// matches the manifest and loads
+pub fn install_puzzle_verified(
+    install: &Installation,
+    source: &dyn PuzzleSource,
+    info: &PuzzleManifestInfo,
+    received: &AtomicU64,
+) -> Result<u64, VertexError> {
+    let dirname = install.puzzle_dir(&info.short_name);
+    if !std::fs::exists(&dirname)? {
+        std::fs::create_dir_all(&dirname)?;
+    }
+
+    fetch_verified(
+        source,
+        &puzzle_file_path(&info.short_name),
+        &install.puzzle_file(&info.short_name),
+        info.hash.as_deref(),
+        info.size_limit(),
+        received,
+        |part| puzzle_from_file(part).map(|_| ()),
+    )
+}
+
+// fetches a manifest and its signature next to dest, without replacing
+// it, and checks the signature. the new manifest only takes effect once
+// commit_manifest is called
+pub fn fetch_manifest(
+    source: &dyn PuzzleSource,
+    dest: &Path,
+    verifier: &ManifestVerifier,
+) -> Result<Manifest, VertexError> {
+    let part = partial_file(dest);
+    let sig_part = signature_file(&part);
+
+    let _ = std::fs::remove_file(&sig_part);
+    let received = AtomicU64::default();
+    source.fetch(MANIFEST_PATH, &part, MAX_MANIFEST_SIZE, &received)?;
+    if let Err(e) = source.fetch(
+        MANIFEST_SIGNATURE_PATH,
+        &sig_part,
+        MAX_SIGNATURE_SIZE,
+        &received,
+    ) {
+        // fine for a trusted source; otherwise verification says why
+        info!("No manifest signature: {:?}", e);
+        let _ = std::fs::remove_file(&sig_part);
+    }
+
+    let manifest = Manifest::from_signed_file(&part, verifier);
+    if manifest.is_err() {
+        discard_manifest(dest);
+        source.forget(MANIFEST_PATH);
+        source.forget(MANIFEST_SIGNATURE_PATH);
+    }
+    manifest
+}
+
+pub fn commit_manifest(dest: &Path) -> Result<(), VertexError> {
+    let sig_part = signature_file(&partial_file(dest));
+    if std::fs::exists(&sig_part)? {
+        std::fs::rename(&sig_part, signature_file(dest))?;
+    } else if std::fs::exists(signature_file(dest))? {
+        std::fs::remove_file(signature_file(dest))?;
+    }
+    std::fs::rename(partial_file(dest), dest)?;
+    Ok(())
+}
+
+fn discard_manifest(dest: &Path) {
+    let _ = std::fs::remove_file(partial_file(dest));
+    let _ = std::fs::remove_file(signature_file(&partial_file(dest)));
+}
+
+fn revert_failed(old: &Manifest, new: &Manifest, failed: &[(String, VertexError)]) -> Manifest {
+    let failed: HashSet<&str> = failed.iter().map(|(name, _)| name.as_str()).collect();
+    Manifest {
+        puzzles: new
+            .puzzles
+            .iter()
+            .filter_map(|info| match failed.contains(info.short_name.as_str()) {
+                true => old.find(&info.short_name).cloned(),
+                false => Some(info.clone()),
+            })
+            .collect(),
+        ..new.clone()
+    }
+}
+
+// brings the installed manifest and puzzles up to date with the source,
+// fetching only puzzles which are new or have changed. with force, every
+// puzzle is fetched again
+pub fn sync_puzzles(
+    install: &Installation,
+    source: &dyn PuzzleSource,
+    verifier: &ManifestVerifier,
+    force: bool,
+    progress: &SyncProgress,
+) -> Result<SyncReport, VertexError> {
+    let manifest_path = install.network_manifest();
+    let old = Manifest::from_file(&manifest_path).unwrap_or_default();
+    let mut new = fetch_manifest(source, &manifest_path, verifier)?;
+
+    let mut report = SyncReport::default();
+    let mut jobs = Vec::new();
+
+    // short names become paths in the installation, so a puzzle with a bad
+    // one is left out of the installed manifest entirely
+    new.puzzles.retain(|info| {
+        let valid = is_valid_name(&info.short_name);
+        if !valid {
+            warn!("Skipping puzzle with invalid name \"{}\"", info.short_name);
+            let e = VertexError::Str("Invalid puzzle name".to_string());
+            report.failed.push((info.short_name.clone(), e));
+        }
+        valid
+    });
+
+    for info in &new.puzzles {
+        // a copy which can't be read is replaced, like an outdated one,
+        // rather than stopping the whole sync
+        let step = match local_hash(&install.puzzle_file(&info.short_name)) {
+            Ok(local) => sync_step(old.find(&info.short_name), info, local.as_deref()),
+            Err(e) => {
+                warn!("Can't read installed {}: {:?}", info.short_name, e);
+                SyncStep::Update
+            }
+        };
+        let step = match step {
+            SyncStep::Keep if force => SyncStep::Update,
+            step => step,
+        };
+
+        if step == SyncStep::Keep {
+            report.unchanged += 1;
+        } else {
+            jobs.push((info, step));
+        }
+    }
+
+    progress
+        .puzzles_total
+        .fetch_add(jobs.len(), Ordering::Relaxed);
+
+    // workers take the next job until there are none left; results are
+    // kept in manifest order so the report doesn't depend on timing
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<u64, VertexError>>>> =
+        Mutex::new((0..jobs.len()).map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..MAX_CONCURRENT_DOWNLOADS.min(jobs.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some((info, step)) = jobs.get(i) else {
+                    break;
+                };
+                info!("Installing {} ({:?})", info.short_name, step);
+                let result = install_puzzle_verified(install, source, info, &progress.bytes);
+                progress.puzzles_done.fetch_add(1, Ordering::Relaxed);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    let results = results.into_inner().unwrap();
+    for ((info, step), result) in jobs.iter().zip(results) {
+        let name = info.short_name.clone();
+        match result.expect("every job is run") {
+            Ok(_) if *step == SyncStep::Add => report.added.push(name),
+            Ok(_) => report.updated.push(name),
+            Err(e) => {
+                error!("Failed to install {}: {:?}", name, e);
+                report.failed.push((name, e));
+            }
+        }
+    }
+
+    report.removed = old
+        .puzzles
+        .iter()
+        .filter(|p| new.find(&p.short_name).is_none())
+        .map(|p| p.short_name.clone())
+        .collect();
+
+    // the installed manifest describes the installed files, so a puzzle
+    // which failed keeps its old entry, or none, and is tried again next
+    // time. its signature no longer matches, but it was already checked
+    if !report.failed.is_empty() {
+        let part = partial_file(&manifest_path);
+        save_to_file(&revert_failed(&old, &new, &report.failed), &part)?;
+        let _ = std::fs::remove_file(signature_file(&part));
+    }
+
+    commit_manifest(&manifest_path)?;
+
+    info!("Synced puzzles: {}", report.summary());
+    Ok(report)
+}
+
+// syncs the source's own puzzles, then every pack its manifest lists. a
+// pack which fails to sync is reported without stopping the others
+pub fn sync_packs(
+    install: &Installation,
+    source: &dyn PuzzleSource,
+    verifier: &ManifestVerifier,
+    force: bool,
+    progress: &SyncProgress,
+) -> Result<SyncReport, VertexError> {
+    let mut report = sync_puzzles(install, source, verifier, force, progress)?;
+    let root = Manifest::from_file(&install.network_manifest())?;
+    let cache = FetchCache::new(install.fetch_cache());
+
+    for info in &root.packs {
+        if !is_valid_pack_name(&info.name) {
+            warn!("Skipping pack with invalid name \"{}\"", info.name);
+            continue;
+        }
+
+        let pack_install = install.pack(&info.name);
+        let pack_source = pack_source(source, info, Some(cache.clone()));
+        info!("Syncing pack {} from {}", info.name, pack_source.describe());
+
+        let result = [pack_install.puzzles(), pack_install.save_data()]
+            .iter()
+            .try_for_each(std::fs::create_dir_all)
+            .map_err(VertexError::from)
+            .and_then(|_| {
+                sync_puzzles(
+                    &pack_install,
+                    pack_source.as_ref(),
+                    verifier,
+                    force,
+                    progress,
+                )
+            });
+
+        match result {
+            Ok(pack_report) => report.merge(&info.name, pack_report),
+            Err(e) => {
+                error!("Failed to sync pack {}: {:?}", info.name, e);
+                report.failed.push((format!("{} pack", info.name), e));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(version: u32, hash: Option<&str>) -> PuzzleManifestInfo {
+        PuzzleManifestInfo {
+            version,
+            hash: hash.map(str::to_string),
+            ..PuzzleManifestInfo::new("rose", "Rose")
+        }
+    }
+
+    #[test]
+    fn hashes_are_hex_sha256() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn steps() {
+        let new = info(2, None);
+        assert_eq!(sync_step(None, &new, None), SyncStep::Add);
+        assert_eq!(
+            sync_step(Some(&info(2, None)), &new, Some("x")),
+            SyncStep::Keep
+        );
+        assert_eq!(
+            sync_step(Some(&info(1, None)), &new, Some("x")),
+            SyncStep::Update
+        );
+        assert_eq!(sync_step(None, &new, Some("x")), SyncStep::Update);
+
+        // a hash outranks the version
+        let new = info(2, Some("ABC"));
+        assert_eq!(
+            sync_step(Some(&info(1, None)), &new, Some("abc")),
+            SyncStep::Keep
+        );
+        assert_eq!(
+            sync_step(Some(&info(2, None)), &new, Some("def")),
+            SyncStep::Update
+        );
+    }
+
+    #[test]
+    fn manifests_cant_raise_the_size_limit() {
+        let huge = PuzzleManifestInfo {
+            max_size: Some(u64::MAX),
+            ..info(1, None)
+        };
+        assert_eq!(huge.size_limit(), MAX_PUZZLE_SIZE);
+        let small = PuzzleManifestInfo {
+            max_size: Some(10),
+            ..info(1, None)
+        };
+        assert_eq!(small.size_limit(), 10);
+    }
+
+    fn write_source(source: &mut MemorySource, puzzles: &[(&str, &str)]) -> Manifest {
+        let mut manifest = Manifest::default();
+        for (short_name, contents) in puzzles {
+            source.insert(puzzle_file_path(short_name), *contents);
+            manifest.puzzles.push(PuzzleManifestInfo {
+                hash: Some(sha256_hex(contents.as_bytes())),
+                ..PuzzleManifestInfo::new(*short_name, *short_name)
+            });
+        }
+        source.insert(MANIFEST_PATH, serde_yaml::to_string(&manifest).unwrap());
+        manifest
+    }
+
+    #[test]
+    fn syncs_only_changes() {
+        let root = std::env::temp_dir().join("vertex_sync_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let install = Installation::initialize(root).unwrap();
+        let mut source = MemorySource::new();
+
+        let empty = serde_yaml::to_string(&PuzzleFileStorage::default()).unwrap();
+        let changed = serde_yaml::to_string(&PuzzleFileStorage {
+            edges: vec![(0, 1)],
+            ..default()
+        })
+        .unwrap();
+
+        write_source(&mut source, &[("a", &empty), ("b", &empty)]);
+        let progress = SyncProgress::default();
+        let report = sync_puzzles(
+            &install,
+            &source,
+            &ManifestVerifier::trusted(),
+            false,
+            &progress,
+        )
+        .unwrap();
+        assert_eq!(report.added, vec!["a", "b"]);
+        assert_eq!(progress.puzzles_done(), 2);
+        assert_eq!(progress.bytes(), 2 * empty.len() as u64);
+
+        write_source(
+            &mut source,
+            &[("a", &empty), ("c", &changed), ("b", &changed)],
+        );
+        let report = sync_puzzles(
+            &install,
+            &source,
+            &ManifestVerifier::trusted(),
+            false,
+            &SyncProgress::default(),
+        )
+        .unwrap();
+        assert_eq!(report.added, vec!["c"]);
+        assert_eq!(report.updated, vec!["b"]);
+        assert_eq!(report.unchanged, 1);
+        assert!(report.removed.is_empty());
+
+        write_source(&mut source, &[("c", &changed)]);
+        let report = sync_puzzles(
+            &install,
+            &source,
+            &ManifestVerifier::trusted(),
+            false,
+            &SyncProgress::default(),
+        )
+        .unwrap();
+        assert_eq!(report.removed, vec!["a", "b"]);
+        assert_eq!(report.unchanged, 1);
+    }
+
+    #[test]
+    fn failed_updates_are_retried() {
+        let root = std::env::temp_dir().join("vertex_sync_retry_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let install = Installation::initialize(root).unwrap();
+        let verifier = ManifestVerifier::trusted();
+        let mut source = MemorySource::new();
+
+        let empty = serde_yaml::to_string(&PuzzleFileStorage::default()).unwrap();
+        let changed = serde_yaml::to_string(&PuzzleFileStorage {
+            edges: vec![(0, 1)],
+            ..default()
+        })
+        .unwrap();
+
+        // without a hash, only the version says whether a puzzle changed
+        let mut manifest = Manifest {
+            puzzles: vec![PuzzleManifestInfo::new("a", "a")],
+            ..default()
+        };
+        source.insert(puzzle_file_path("a"), empty);
+        source.insert(MANIFEST_PATH, serde_yaml::to_string(&manifest).unwrap());
+        let progress = SyncProgress::default();
+        let report = sync_puzzles(&install, &source, &verifier, false, &progress).unwrap();
+        assert_eq!(report.added, vec!["a"]);
+
+        manifest.puzzles[0].version += 1;
+        source.insert(MANIFEST_PATH, serde_yaml::to_string(&manifest).unwrap());
+        source.insert(puzzle_file_path("a"), "vertices: [");
+        let report = sync_puzzles(&install, &source, &verifier, false, &progress).unwrap();
+        assert_eq!(report.failed.len(), 1);
+
+        source.insert(puzzle_file_path("a"), changed);
+        let report = sync_puzzles(&install, &source, &verifier, false, &progress).unwrap();
+        assert_eq!(report.updated, vec!["a"]);
+    }
+
+    #[test]
+    fn invalid_names_are_skipped() {
+        let root = std::env::temp_dir().join("vertex_sync_names_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let install = Installation::initialize(root.join("install")).unwrap();
+        let mut source = MemorySource::new();
+
+        let good = serde_yaml::to_string(&PuzzleFileStorage::default()).unwrap();
+        write_source(&mut source, &[("a", &good), ("../escaped", &good)]);
+        let report = sync_puzzles(
+            &install,
+            &source,
+            &ManifestVerifier::trusted(),
+            false,
+            &SyncProgress::default(),
+        )
+        .unwrap();
+
+        assert_eq!(report.added, vec!["a"]);
+        assert_eq!(report.failed.len(), 1);
+        assert!(!std::fs::exists(root.join("install/escaped")).unwrap());
+        let installed = Manifest::from_file(&install.network_manifest()).unwrap();
+        assert!(installed.find("../escaped").is_none());
+        assert!(installed.find("a").is_some());
+    }
+
+    #[test]
+    fn unreadable_copies_dont_stop_the_sync() {
+        let root = std::env::temp_dir().join("vertex_sync_unreadable_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let install = Installation::initialize(root).unwrap();
+        let verifier = ManifestVerifier::trusted();
+        let mut source = MemorySource::new();
+
+        let good = serde_yaml::to_string(&PuzzleFileStorage::default()).unwrap();
+        write_source(&mut source, &[("a", &good), ("b", &good)]);
+        let progress = SyncProgress::default();
+        sync_puzzles(&install, &source, &verifier, false, &progress).unwrap();
+
+        // a directory where the puzzle should be can't be read or replaced
+        let path = install.puzzle_file("a");
+        std::fs::remove_file(&path).unwrap();
+        std::fs::create_dir_all(path.join("in_the_way")).unwrap();
+
+        let report = sync_puzzles(&install, &source, &verifier, false, &progress).unwrap();
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, "a");
+        assert_eq!(report.unchanged, 1);
+        let manifest_path = install.network_manifest();
+        assert!(!std::fs::exists(partial_file(&manifest_path)).unwrap());
+    }
+
+    #[test]
+    fn bad_downloads_keep_the_old_file() {
+        let root = std::env::temp_dir().join("vertex_sync_mismatch_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let install = Installation::initialize(root).unwrap();
+        let mut source = MemorySource::new();
+
+        let good = serde_yaml::to_string(&PuzzleFileStorage::default()).unwrap();
+        let mut manifest = write_source(&mut source, &[("a", &good)]);
+        sync_puzzles(
+            &install,
+            &source,
+            &ManifestVerifier::trusted(),
+            false,
+            &SyncProgress::default(),
+        )
+        .unwrap();
+
+        manifest.puzzles[0].hash = Some(sha256_hex(b"something else"));
+        let err = install_puzzle_verified(
+            &install,
+            &source,
+            &manifest.puzzles[0],
+            &AtomicU64::default(),
+        );
+        assert!(err.is_err());
+
+        source.remove(&puzzle_file_path("a"));
+        source.insert(MANIFEST_PATH, serde_yaml::to_string(&manifest).unwrap());
+        let report = sync_puzzles(
+            &install,
+            &source,
+            &ManifestVerifier::trusted(),
+            false,
+            &SyncProgress::default(),
+        )
+        .unwrap();
+        assert_eq!(report.failed.len(), 1);
+        assert!(report.failure_summary().unwrap().contains("a ("));
+
+        let installed = std::fs::read_to_string(install.puzzle_file("a")).unwrap();
+        assert_eq!(installed, good);
+        assert!(!std::fs::exists(partial_file(&install.puzzle_file("a"))).unwrap());
+        assert!(matches!(
+            err,
+            Err(VertexError::Integrity(IntegrityError::HashMismatch { .. }))
+        ));
+    }
+
+    #[test]
+    fn unsigned_manifests_are_refused() {
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+
+        let root = std::env::temp_dir().join("vertex_sync_signature_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let install = Installation::initialize(root).unwrap();
+        let mut source = MemorySource::new();
+
+        let keypair = Ed25519KeyPair::from_seed_unchecked(&[3; 32]).unwrap();
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let key: String = hex(keypair.public_key().as_ref());
+        let verifier = ManifestVerifier::new(&key);
+
+        let empty = serde_yaml::to_string(&PuzzleFileStorage::default()).unwrap();
+        let manifest = write_source(&mut source, &[("a", &empty)]);
+        let contents = serde_yaml::to_string(&manifest).unwrap();
+        let signature: String = hex(keypair.sign(contents.as_bytes()).as_ref());
+        source.insert(MANIFEST_SIGNATURE_PATH, signature);
+
+        let progress = SyncProgress::default();
+        let report = sync_puzzles(&install, &source, &verifier, false, &progress).unwrap();
+        assert_eq!(report.added, vec!["a"]);
+
+        // a tampered manifest is refused, and the installed one kept
+        write_source(&mut source, &[("a", &empty), ("b", &empty)]);
+        assert!(sync_puzzles(&install, &source, &verifier, false, &progress).is_err());
+        let installed = Manifest::from_file(&install.network_manifest()).unwrap();
+        assert_eq!(installed, manifest);
+
+        source.remove(MANIFEST_SIGNATURE_PATH);
+        assert!(sync_puzzles(&install, &source, &verifier, false, &progress).is_err());
+    }
+
+    #[test]
+    fn reverted_manifests_hold_only_checked_entries() {
+        use ring::signature::{Ed25519KeyPair, KeyPair};
+
+        let root = std::env::temp_dir().join("vertex_sync_revert_signed_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let install = Installation::initialize(root).unwrap();
+        let mut source = MemorySource::new();
+
+        let keypair = Ed25519KeyPair::from_seed_unchecked(&[5; 32]).unwrap();
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let key: String = hex(keypair.public_key().as_ref());
+        let verifier = ManifestVerifier::new(&key);
+        let sign = |source: &mut MemorySource, manifest: &Manifest| {
+            let contents = serde_yaml::to_string(manifest).unwrap();
+            let signature: String = hex(keypair.sign(contents.as_bytes()).as_ref());
+            source.insert(MANIFEST_SIGNATURE_PATH, signature);
+        };
+
+        let empty = serde_yaml::to_string(&PuzzleFileStorage::default()).unwrap();
+        let changed = serde_yaml::to_string(&PuzzleFileStorage {
+            edges: vec![(0, 1)],
+            ..default()
+        })
+        .unwrap();
+
+        let old = write_source(&mut source, &[("a", &empty)]);
+        sign(&mut source, &old);
+        let progress = SyncProgress::default();
+        sync_puzzles(&install, &source, &verifier, false, &progress).unwrap();
+
+        // "a" changes but its download fails, while "b" installs
+        let new = write_source(&mut source, &[("a", &changed), ("b", &empty)]);
+        sign(&mut source, &new);
+        source.insert(puzzle_file_path("a"), "vertices: [");
+        let report = sync_puzzles(&install, &source, &verifier, false, &progress).unwrap();
+        assert_eq!(report.added, vec!["b"]);
+        assert_eq!(report.failed.len(), 1);
+
+        // the installed manifest is unsigned, but every entry in it came
+        // from a manifest which was
+        let path = install.network_manifest();
+        assert!(!std::fs::exists(signature_file(&path)).unwrap());
+        assert!(Manifest::from_signed_file(&path, &verifier).is_err());
+        let installed = Manifest::from_file(&path).unwrap();
+        assert_eq!(installed.find("a"), old.find("a"));
+        assert_eq!(installed.find("b"), new.find("b"));
+
+        // and the next sync checks the source's manifest as usual
+        source.insert(puzzle_file_path("a"), changed);
+        let report = sync_puzzles(&install, &source, &verifier, false, &progress).unwrap();
+        assert_eq!(report.updated, vec!["a"]);
+        let installed = Manifest::from_signed_file(&path, &verifier).unwrap();
+        assert_eq!(installed, new);
+    }
+
+    #[test]
+    fn syncs_packs() {
+        let root = std::env::temp_dir().join("vertex_sync_packs_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let install = Installation::initialize(root).unwrap();
+        let verifier = ManifestVerifier::trusted();
+        let mut source = MemorySource::new();
+
+        let empty = serde_yaml::to_string(&PuzzleFileStorage::default()).unwrap();
+        let mut manifest = write_source(&mut source, &[("a", &empty)]);
+        manifest.packs = vec![
+            PackInfo {
+                name: "winter".to_string(),
+                title: "Winter".to_string(),
+                source: None,
+            },
+            PackInfo {
+                name: "missing".to_string(),
+                title: "Missing".to_string(),
+                source: None,
+            },
+        ];
+        source.insert(MANIFEST_PATH, serde_yaml::to_string(&manifest).unwrap());
+
+        let mut winter = MemorySource::new();
+        let winter_manifest = write_source(&mut winter, &[("a", &empty), ("b", &empty)]);
+        source.insert(
+            format!("winter/{}", MANIFEST_PATH),
+            serde_yaml::to_string(&winter_manifest).unwrap(),
+        );
+        for name in ["a", "b"] {
+            source.insert(format!("winter/{}", puzzle_file_path(name)), empty.clone());
+        }
+
+        let progress = SyncProgress::default();
+        let report = sync_packs(&install, &source, &verifier, false, &progress).unwrap();
+        assert_eq!(report.added, vec!["a", "winter/a", "winter/b"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(progress.puzzles_total(), 3);
+
+        assert!(std::fs::exists(install.pack("winter").puzzle_file("b")).unwrap());
+        let packs = PuzzlePacks::from_install(&install).unwrap();
+        assert_eq!(packs.0.len(), 2);
+        assert!(packs.get(&PuzzleKey::parse("winter/b")).is_some());
+    }
+
+    #[test]
+    fn oversized_downloads_are_rejected() {
+        let root = std::env::temp_dir().join("vertex_sync_size_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let install = Installation::initialize(root).unwrap();
+        let mut source = MemorySource::new();
+
+        let good = serde_yaml::to_string(&PuzzleFileStorage::default()).unwrap();
+        let mut manifest = write_source(&mut source, &[("a", &good)]);
+        manifest.puzzles[0].hash = None;
+        manifest.puzzles[0].max_size = Some(4);
+
+        let err = install_puzzle_verified(
+            &install,
+            &source,
+            &manifest.puzzles[0],
+            &AtomicU64::default(),
+        );
+        assert!(matches!(
+            err,
+            Err(VertexError::Integrity(IntegrityError::TooLarge {
+                limit: 4,
+                ..
+            }))
+        ));
+        assert!(!std::fs::exists(install.puzzle_file("a")).unwrap());
+    }
+}
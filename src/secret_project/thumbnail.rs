@@ -0,0 +1,154 @@
+use crate::secret_project::*;
+use image::{Rgba, RgbaImage};
+
+pub const THUMBNAIL_SIZE: u32 = 96;
+
+const THUMBNAIL_PADDING: f32 = 4.0;
+
+fn to_rgba(color: Srgba) -> Rgba<u8> {
+    let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Rgba([
+        byte(color.red),
+        byte(color.green),
+        byte(color.blue),
+        byte(color.alpha),
+    ])
+}
+
+// unrevealed faces keep a hint of their brightness, but no colour
+fn greyed_out(color: Srgba) -> Srgba {
+    let l = 0.299 * color.red + 0.587 * color.green + 0.114 * color.blue;
+    Srgba::gray(l).mix(&Srgba::gray(0.9), 0.6)
+}
+
+fn fill_polygon(img: &mut RgbaImage, points: &[Vec2], color: Rgba<u8>) {
+    let min = points.iter().copied().fold(Vec2::INFINITY, Vec2::min);
+    let max = points.iter().copied().fold(Vec2::NEG_INFINITY, Vec2::max);
+
+    let x0 = min.x.floor().max(0.0) as u32;
+    let y0 = min.y.floor().max(0.0) as u32;
+    let x1 = (max.x.ceil() as u32).min(img.width());
+    let y1 = (max.y.ceil() as u32).min(img.height());
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let center = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            if point_in_polygon(center, points) {
+                img.put_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+// draws a square picture of the puzzle without a gpu. revealed faces are
+// drawn in colour, the rest in grey.
+pub fn render_thumbnail(puzzle: &Puzzle, save: &SaveData, size: u32) -> RgbaImage {
+    let mut img = RgbaImage::from_pixel(size, size, to_rgba(Srgba::gray(0.9)));
+
+    let mut min = Vec2::INFINITY;
+    let mut max = Vec2::NEG_INFINITY;
+    for (_, v) in puzzle.vertices() {
+        min = min.min(v.pos);
+        max = max.max(v.pos);
+    }
+
+    if min.x >= max.x && min.y >= max.y {
+        return img;
+    }
+
+    // fit the longer side, centered, with the image y-down
+    let extent = (max - min).max_element();
+    let scale = (size as f32 - 2.0 * THUMBNAIL_PADDING) / extent;
+    let offset = (Vec2::splat(size as f32) - (max - min) * scale) / 2.0;
+    let to_pixel = |p: Vec2| {
+        let q = (p - min) * scale + offset;
+        Vec2::new(q.x, size as f32 - q.y)
+    };
+
+    for (points, color, revealed) in puzzle.faces_with_progress(save) {
+        let points: Vec<Vec2> = points.into_iter().map(to_pixel).collect();
+        let color = if revealed { color } else { greyed_out(color) };
+        fill_polygon(&mut img, &points, to_rgba(color));
+    }
+
+    img
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).ok()?.modified().ok()
+}
+
+// the cached thumbnail for an installed puzzle, redrawn whenever the
+// puzzle or its save data is newer than the cache
+pub fn load_thumbnail(install: &Installation, short_name: &str) -> Result<RgbaImage, VertexError> {
+    let path = install.thumbnail_file(short_name);
+    let puzzle_path = install.puzzle_file(short_name);
+    let save_path = install.save_data_file(short_name);
+
+    if let Some(cached) = modified(&path) {
+        let is_fresh = [&puzzle_path, &save_path]
+            .iter()
+            .filter_map(|p| modified(p))
+            .all(|t| t <= cached);
+
+        if is_fresh {
+            if let Ok(img) = image::open(&path) {
+                return Ok(img.into_rgba8());
+            }
+        }
+    }
+
+    let (puzzle, _) = puzzle_from_file(&puzzle_path)?;
+    let save = SaveData::from_file(&save_path)?;
+    let img = render_thumbnail(&puzzle, &save, THUMBNAIL_SIZE);
+
+    if let Err(e) = img.save(&path) {
+        warn!("Failed to cache thumbnail at {}: {:?}", path.display(), e);
+    }
+
+    Ok(img)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle() -> (Puzzle, [usize; 3]) {
+        let mut puzzle = Puzzle::default();
+        let a = puzzle.add_point(Vec2::new(0.0, 0.0));
+        let b = puzzle.add_point(Vec2::new(100.0, 0.0));
+        let c = puzzle.add_point(Vec2::new(0.0, 100.0));
+        for (u, v) in [(a, b), (b, c), (a, c)] {
+            puzzle.add_solution_edge(u, v);
+        }
+        let key = puzzle.face_keys().pop().unwrap();
+        puzzle.set_face(key, Some(Srgba::RED));
+        (puzzle, [a, b, c])
+    }
+
+    #[test]
+    fn revealed_faces_are_colored() {
+        let (puzzle, [a, b, c]) = triangle();
+        let mut save = SaveData::default();
+        for (u, v) in [(a, b), (b, c), (a, c)] {
+            save.edges.add_edge(u, v);
+        }
+
+        let img = render_thumbnail(&puzzle, &save, 64);
+
+        // the right angle is at the bottom left of the image
+        assert_eq!(*img.get_pixel(10, 53), Rgba([255, 0, 0, 255]));
+        // across the hypotenuse is background
+        assert_eq!(*img.get_pixel(53, 10), to_rgba(Srgba::gray(0.9)));
+    }
+
+    #[test]
+    fn hidden_faces_are_grey() {
+        let (puzzle, _) = triangle();
+        let img = render_thumbnail(&puzzle, &SaveData::default(), 64);
+
+        let Rgba([r, g, b, _]) = *img.get_pixel(10, 53);
+        assert_eq!(r, g);
+        assert_eq!(g, b);
+    }
+}
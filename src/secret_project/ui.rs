@@ -1,3 +1,5 @@
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::ui::RelativeCursorPosition;
 
 use crate::secret_project::*;
@@ -7,6 +9,9 @@ pub struct UiPlugin;
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<UiMessage>();
+        app.init_resource::<MenuSearch>();
+        app.init_resource::<MenuThumbnails>();
+        app.init_resource::<MenuPack>();
         app.add_systems(Update, (button_interactions, handle_ui_messages));
 
         // editor/playing menu
@@ -16,11 +21,26 @@ impl Plugin for UiPlugin {
         // main menu
         app.add_systems(OnEnter(AppState::Menu), spawn_main_menu);
         app.add_systems(OnExit(AppState::Menu), despawn_main_menu);
+        app.add_systems(
+            Update,
+            (
+                type_search_query,
+                update_puzzle_list,
+                scroll_puzzle_list,
+                update_sync_status_text,
+            )
+                .chain()
+                .run_if(in_state(AppState::Menu)),
+        );
 
         // victory screen
         app.add_systems(OnEnter(VictoryScreen), spawn_victory_screen);
         app.add_systems(OnExit(VictoryScreen), despawn_victory_screen);
 
+        // pause menu
+        app.add_systems(OnEnter(PauseScreen), spawn_pause_menu);
+        app.add_systems(OnExit(PauseScreen), despawn_pause_menu);
+
         // loading
         app.add_systems(OnEnter(AppState::Loading), spawn_loading_screen);
         app.add_systems(OnExit(AppState::Loading), begin_loading_fade_out);
@@ -34,7 +54,7 @@ impl Plugin for UiPlugin {
     }
 }
 
-#[derive(Message, Component, Debug, Clone, Copy)]
+#[derive(Message, Component, Debug, Clone)]
 pub enum UiMessage {
     Previous,
     Next,
@@ -46,8 +66,15 @@ pub enum UiMessage {
     CloseMenu,
     SetEditorMode(EditorMode),
     Autosolver,
-    OpenPuzzle(usize),
+    Options,
+    OpenPuzzle(PuzzleKey),
+    SelectPack(String),
     CloseVictoryScreen,
+    Pause,
+    Resume,
+    RestartPuzzle,
+    ExportPicture,
+    SyncPuzzles,
     ExitToDesktop,
     DespawnEntity(Entity),
 }
@@ -88,7 +115,7 @@ fn button_interactions(
         debug!("{}: {:?} {:?}", e, interaction, msg);
         match interaction {
             Interaction::Pressed => {
-                commands.write_message(*msg);
+                commands.write_message(msg.clone());
                 commands.write_message(SoundEffect::LightPop);
                 color.0 = PRESSED_BUTTON_COLOR;
             }
@@ -197,7 +224,9 @@ fn footer_bar(commands: &mut Commands, font: &TextFont) {
                 ("Images", UiMessage::SetEditorMode(EditorMode::Images)),
                 ("Select", UiMessage::SetEditorMode(EditorMode::Select)),
                 ("Eraser", UiMessage::SetEditorMode(EditorMode::Eraser)),
+                ("Move", UiMessage::SetEditorMode(EditorMode::Move)),
                 ("Autosolver", UiMessage::Autosolver),
+                ("Options", UiMessage::Options),
             ];
 
             for (name, msg) in button_names {
@@ -212,22 +241,25 @@ fn handle_ui_messages(
     mut messages: MessageReader<UiMessage>,
     mut save: ResMut<SaveData>,
     mut solver: ResMut<Autosolver>,
+    mut options: ResMut<OptionsMenu>,
     current: Res<CurrentPuzzle>,
+    packs: Res<PuzzlePacks>,
+    mut menu_pack: ResMut<MenuPack>,
+    mut titles: Query<&mut RevealedText, With<UiTitle>>,
+    app_state: Res<State<AppState>>,
 ) {
     for msg in messages.read() {
         match msg {
             UiMessage::Previous => {
                 state.set(AppState::Playing { victory: false });
-                if let Some(id) = current.0 {
-                    if id > 0 {
-                        commands.write_message(OpenPuzzleById(id - 1));
-                    }
+                if let Some(key) = current.0.as_ref().and_then(|k| packs.neighbor(k, -1)) {
+                    commands.write_message(OpenPuzzleByKey(key));
                 }
             }
             UiMessage::Next => {
                 state.set(AppState::Playing { victory: false });
-                if let Some(id) = current.0 {
-                    commands.write_message(OpenPuzzleById(id + 1));
+                if let Some(key) = current.0.as_ref().and_then(|k| packs.neighbor(k, 1)) {
+                    commands.write_message(OpenPuzzleByKey(key));
                 }
             }
             UiMessage::Save => (),
@@ -241,13 +273,19 @@ fn handle_ui_messages(
             UiMessage::SetEditorMode(m) => {
                 state.set(AppState::Editing { mode: *m });
             }
+            UiMessage::Options => {
+                options.open = !options.open;
+            }
             UiMessage::Autosolver => {
                 solver.toggle();
                 commands.write_message(TextMessage::debug("Toggled Autosolver"));
             }
-            UiMessage::OpenPuzzle(id) => {
+            UiMessage::OpenPuzzle(key) => {
                 state.set(AppState::Playing { victory: false });
-                commands.write_message(OpenPuzzleById(*id));
+                commands.write_message(OpenPuzzleByKey(key.clone()));
+            }
+            UiMessage::SelectPack(name) => {
+                menu_pack.0 = name.clone();
             }
             UiMessage::CloseMenu => {
                 state.set(AppState::Playing { victory: false });
@@ -255,6 +293,30 @@ fn handle_ui_messages(
             UiMessage::CloseVictoryScreen => {
                 state.set(AppState::Playing { victory: false });
             }
+            UiMessage::Pause => {
+                if app_state.is_paused() {
+                    state.set(app_state.resumed());
+                } else if let Some(paused) = app_state.paused() {
+                    state.set(paused);
+                }
+            }
+            UiMessage::Resume => {
+                state.set(app_state.resumed());
+            }
+            UiMessage::RestartPuzzle => {
+                save.edges.clear();
+                save.is_complete = false;
+                for mut title in &mut titles {
+                    title.reset(&save.revealed_title);
+                }
+                state.set(app_state.resumed());
+            }
+            UiMessage::ExportPicture => {
+                commands.write_message(ExportSvg(SvgMode::Progress));
+            }
+            UiMessage::SyncPuzzles => {
+                commands.write_message(NetworkFetch);
+            }
             UiMessage::ExitToDesktop => {
                 commands.write_message(AppExit::Success);
             }
@@ -360,6 +422,36 @@ fn vspace(height: f32) -> impl Bundle {
 #[derive(Component)]
 struct MenuRoot;
 
+#[derive(Component)]
+struct PuzzleList;
+
+#[derive(Component)]
+struct SearchText;
+
+// what's been typed into the main menu's search box
+#[derive(Resource, Default)]
+struct MenuSearch(String);
+
+// loaded once per visit to the menu, rather than every time the list changes
+#[derive(Resource, Default)]
+struct MenuThumbnails(HashMap<PuzzleKey, Option<Handle<Image>>>);
+
+#[derive(Component)]
+struct PackBar;
+
+// which pack's puzzles the main menu lists
+#[derive(Resource)]
+struct MenuPack(String);
+
+impl Default for MenuPack {
+    fn default() -> Self {
+        Self(DEFAULT_PACK.to_string())
+    }
+}
+
+#[derive(Component)]
+struct SyncStatusText;
+
 #[derive(Component)]
 struct NetworkRoot;
 
@@ -407,7 +499,66 @@ fn standard_menu() -> impl Bundle {
     )
 }
 
-fn main_menu(commands: &mut Commands, font: &TextFont, manifest: &Manifest) {
+fn puzzle_button(
+    s: impl Into<String>,
+    details: Option<String>,
+    font: &TextFont,
+    msg: UiMessage,
+    thumbnail: Option<Handle<Image>>,
+) -> impl Bundle {
+    let image = thumbnail.map(|handle| {
+        (
+            ImageNode::new(handle),
+            Node {
+                width: px(THUMBNAIL_SIZE as f32),
+                height: px(THUMBNAIL_SIZE as f32),
+                margin: UiRect::all(px(4.0)),
+                ..default()
+            },
+        )
+    });
+
+    let detail_font = font.clone().with_font_size(16.0);
+
+    (
+        BackgroundColor(BUTTON_COLOR),
+        Node {
+            align_items: AlignItems::Center,
+            border: UiRect::all(px(2.0)),
+            ..default()
+        },
+        Button,
+        BorderRadius::all(px(4.0)),
+        BorderColor::all(GRAY),
+        msg,
+        Children::spawn((
+            SpawnIter(image.into_iter()),
+            Spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    margin: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                Children::spawn((
+                    Spawn((
+                        Text::new(s),
+                        TextColor(BLACK.into()),
+                        font.clone().with_font_size(24.0),
+                    )),
+                    SpawnIter(details.into_iter().map(move |d| {
+                        (
+                            Text::new(d),
+                            TextColor(Srgba::gray(0.25).into()),
+                            detail_font.clone(),
+                        )
+                    })),
+                )),
+            )),
+        )),
+    )
+}
+
+fn main_menu(commands: &mut Commands, font: &TextFont) {
     let header = big_text_node("Secret Project", font);
 
     let root = commands
@@ -427,14 +578,52 @@ fn main_menu(commands: &mut Commands, font: &TextFont, manifest: &Manifest) {
         .spawn(standard_menu())
         .with_children(|parent| {
             parent.spawn(header);
-            for (id, info) in manifest.puzzles.iter().enumerate() {
-                let s = format!("#{}: {}", id, info.title);
-                let b = make_button(s, font, UiMessage::OpenPuzzle(id));
-                parent.spawn(b);
-            }
+
+            // filled in by update_puzzle_list
+            parent.spawn((
+                PackBar,
+                Node {
+                    flex_wrap: FlexWrap::Wrap,
+                    column_gap: px(6.0),
+                    row_gap: px(6.0),
+                    margin: UiRect::bottom(px(12.0)),
+                    ..default()
+                },
+            ));
+
+            parent.spawn((
+                SearchText,
+                Text::new(""),
+                TextColor(Srgba::gray(0.3).into()),
+                font.clone().with_font_size(24.0),
+            ));
+
+            // filled in by update_puzzle_list
+            parent.spawn((
+                PuzzleList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(9.0),
+                    max_height: vh(60.0),
+                    overflow: Overflow::scroll_y(),
+                    ..default()
+                },
+                ScrollPosition::default(),
+            ));
 
             parent.spawn(vspace(30.0));
 
+            parent.spawn((
+                SyncStatusText,
+                Text::new(""),
+                TextColor(Srgba::gray(0.3).into()),
+                font.clone().with_font_size(20.0),
+            ));
+
+            parent.spawn(make_button("Sync Puzzles", font, UiMessage::SyncPuzzles));
+
+            parent.spawn(make_button("Options", font, UiMessage::Options));
+
             parent.spawn(make_button(
                 "Exit to Desktop",
                 font,
@@ -461,14 +650,139 @@ fn despawn_playing_menu(mut commands: Commands, query: Query<Entity, With<Playin
     }
 }
 
-fn spawn_main_menu(
+fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("EBGaramond-Medium.ttf");
+    let font = TextFont::from_font_size(25.0).with_font(font);
+
+    // progress may have changed since the thumbnails were last drawn
+    commands.insert_resource(MenuThumbnails::default());
+    commands.insert_resource(MenuSearch::default());
+    main_menu(&mut commands, &font);
+}
+
+fn menu_thumbnail(
+    thumbnails: &mut MenuThumbnails,
+    install: &Installation,
+    images: &mut Assets<Image>,
+    key: &PuzzleKey,
+) -> Option<Handle<Image>> {
+    if let Some(loaded) = thumbnails.0.get(key) {
+        return loaded.clone();
+    }
+
+    let loaded = match load_thumbnail(&install.pack(&key.pack), &key.short_name) {
+        Ok(img) => {
+            let img = Image::from_dynamic(img.into(), true, RenderAssetUsages::default());
+            Some(images.add(img))
+        }
+        Err(e) => {
+            warn!("No thumbnail for {}: {:?}", key, e);
+            None
+        }
+    };
+    thumbnails.0.insert(key.clone(), loaded.clone());
+    loaded
+}
+
+fn type_search_query(mut keys: MessageReader<KeyboardInput>, mut search: ResMut<MenuSearch>) {
+    for key in keys.read() {
+        if !key.state.is_pressed() {
+            continue;
+        }
+        if key.key_code == KeyCode::Backspace {
+            search.0.pop();
+        } else if let Some(text) = &key.text {
+            search.0.extend(text.chars().filter(|c| !c.is_control()));
+        }
+    }
+}
+
+fn update_puzzle_list(
     mut commands: Commands,
+    search: Res<MenuSearch>,
+    packs: Res<PuzzlePacks>,
+    menu_pack: Res<MenuPack>,
+    mut thumbnails: ResMut<MenuThumbnails>,
+    install: Res<Installation>,
+    mut images: ResMut<Assets<Image>>,
     asset_server: Res<AssetServer>,
-    manifest: Res<Manifest>,
+    list: Single<(Entity, &mut ScrollPosition), With<PuzzleList>>,
+    pack_bar: Single<(Entity, Ref<PackBar>)>,
+    mut text: Single<&mut Text, With<SearchText>>,
 ) {
+    // the menu is spawned afresh each visit, with nothing in the pack bar
+    let (pack_bar, bar) = pack_bar.into_inner();
+    let packs_changed = packs.is_changed() || menu_pack.is_changed() || bar.is_added();
+    if !search.is_changed() && !packs_changed {
+        return;
+    }
+
     let font = asset_server.load("EBGaramond-Medium.ttf");
     let font = TextFont::from_font_size(25.0).with_font(font);
-    main_menu(&mut commands, &font, &manifest);
+
+    // a pack which has since disappeared falls back to the default one
+    let Some(pack) = packs.pack(&menu_pack.0).or(packs.0.first()) else {
+        return;
+    };
+
+    if packs_changed {
+        commands.entity(pack_bar).despawn_children();
+        for p in &packs.0 {
+            let (complete, total) = pack_progress(&install, p);
+            let s = format!("{} ({}/{})", p.title, complete, total);
+            let b = make_button(s, &font, UiMessage::SelectPack(p.name.clone()));
+            let border = match p.name == pack.name {
+                true => BorderColor::all(BLACK),
+                false => BorderColor::all(GRAY),
+            };
+            commands.spawn((b, ChildOf(pack_bar))).insert(border);
+        }
+    }
+
+    let results = search_manifest(&pack.manifest, &search.0);
+
+    text.0 = if search.0.is_empty() {
+        "Type to search, or filter with tag:, by: and difficulty:".to_string()
+    } else {
+        format!("Search: {} ({} found)", search.0, results.len())
+    };
+
+    let (list, mut scroll) = list.into_inner();
+    scroll.0 = Vec2::ZERO;
+    commands.entity(list).despawn_children();
+
+    for id in results {
+        let info = &pack.manifest.puzzles[id];
+        let key = PuzzleKey::new(&pack.name, &info.short_name);
+        let thumbnail = menu_thumbnail(&mut thumbnails, &install, &mut images, &key);
+        let s = format!("#{}: {}", id, info.title);
+        let msg = UiMessage::OpenPuzzle(key);
+        let b = puzzle_button(s, info.details(), &font, msg, thumbnail);
+        commands.spawn((b, ChildOf(list)));
+    }
+}
+
+fn update_sync_status_text(
+    status: Res<SyncStatus>,
+    mut text: Single<&mut Text, With<SyncStatusText>>,
+) {
+    let s = status.label();
+    if text.0 != s {
+        text.0 = s;
+    }
+}
+
+fn scroll_puzzle_list(
+    mut wheel: MessageReader<MouseWheel>,
+    mut list: Single<&mut ScrollPosition, With<PuzzleList>>,
+) {
+    for event in wheel.read() {
+        let dy = match event.unit {
+            MouseScrollUnit::Line => event.y * 40.0,
+            MouseScrollUnit::Pixel => event.y,
+        };
+        list.0.y = (list.0.y - dy).max(0.0);
+    }
 }
 
 fn despawn_main_menu(mut commands: Commands, query: Query<Entity, With<MenuRoot>>) {
@@ -480,9 +794,23 @@ fn despawn_main_menu(mut commands: Commands, query: Query<Entity, With<MenuRoot>
 #[derive(Component)]
 struct VictoryScreenRoot;
 
-fn victory_menu(commands: &mut Commands, font: &TextFont) {
+fn victory_menu(commands: &mut Commands, font: &TextFont, info: Option<&PuzzleManifestInfo>) {
     let header = big_text_node("You did it!", font);
 
+    let small_text = |s: String, size: f32| {
+        (
+            Text::new(s),
+            TextColor(Srgba::gray(0.2).into()),
+            font.clone().with_font_size(size),
+            TextLayout::new_with_justify(Justify::Center),
+            Node {
+                max_width: px(500.0),
+                margin: UiRect::bottom(px(8.0)),
+                ..default()
+            },
+        )
+    };
+
     let root = commands
         .spawn((
             VictoryScreenRoot,
@@ -501,9 +829,44 @@ fn victory_menu(commands: &mut Commands, font: &TextFont) {
         .with_children(|parent| {
             parent.spawn(header);
 
+            if let Some(info) = info {
+                let byline = match &info.author {
+                    Some(author) => format!("{} by {}", info.title, author),
+                    None => info.title.clone(),
+                };
+                parent.spawn(small_text(byline, 28.0));
+                if let Some(published) = &info.published {
+                    parent.spawn(small_text(format!("Published {}", published), 18.0));
+                }
+                if let Some(description) = &info.description {
+                    parent.spawn(small_text(description.clone(), 20.0));
+                }
+
+                let swatches = info.palette_colors().into_iter().map(|color| {
+                    (
+                        Node {
+                            width: px(24.0),
+                            height: px(24.0),
+                            margin: UiRect::all(px(2.0)),
+                            ..default()
+                        },
+                        BackgroundColor(color.into()),
+                        BorderRadius::all(px(4.0)),
+                    )
+                });
+                parent.spawn((
+                    Node {
+                        margin: UiRect::bottom(px(16.0)),
+                        ..default()
+                    },
+                    Children::spawn(SpawnIter(swatches)),
+                ));
+            }
+
             let buttons = [
                 ("Next Puzzle!", UiMessage::Next),
                 ("Stay and Appreciate", UiMessage::CloseVictoryScreen),
+                ("Export Picture", UiMessage::ExportPicture),
                 ("Back to Main Menu", UiMessage::Menu),
             ];
 
@@ -516,10 +879,16 @@ fn victory_menu(commands: &mut Commands, font: &TextFont) {
     commands.entity(root).add_child(w);
 }
 
-fn spawn_victory_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn spawn_victory_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    packs: Res<PuzzlePacks>,
+    current: Res<CurrentPuzzle>,
+) {
     let font = asset_server.load("EBGaramond-Medium.ttf");
     let font = TextFont::from_font_size(25.0).with_font(font);
-    victory_menu(&mut commands, &font);
+    let info = current.0.as_ref().and_then(|key| packs.get(key));
+    victory_menu(&mut commands, &font, info);
 }
 
 fn despawn_victory_screen(mut commands: Commands, query: Query<Entity, With<VictoryScreenRoot>>) {
@@ -528,6 +897,52 @@ fn despawn_victory_screen(mut commands: Commands, query: Query<Entity, With<Vict
     }
 }
 
+#[derive(Component)]
+struct PauseMenuRoot;
+
+fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("EBGaramond-Medium.ttf");
+    let font = TextFont::from_font_size(25.0).with_font(font);
+
+    let buttons = [
+        ("Resume", UiMessage::Resume),
+        ("Options", UiMessage::Options),
+        ("Restart Puzzle", UiMessage::RestartPuzzle),
+        ("Export Picture", UiMessage::ExportPicture),
+        ("Main Menu", UiMessage::Menu),
+        ("Quit", UiMessage::ExitToDesktop),
+    ];
+
+    // the root covers the whole window, so nothing underneath can be clicked
+    commands
+        .spawn((
+            PauseMenuRoot,
+            Node {
+                width: percent(100.0),
+                height: percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(BLACK.with_alpha(0.3).into()),
+            GlobalZIndex(1),
+        ))
+        .with_children(|parent| {
+            parent.spawn(standard_menu()).with_children(|parent| {
+                parent.spawn(big_text_node("Paused", &font));
+                for (s, msg) in buttons {
+                    parent.spawn(make_button(s, &font, msg));
+                }
+            });
+        });
+}
+
+fn despawn_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenuRoot>>) {
+    for e in query {
+        commands.entity(e).despawn();
+    }
+}
+
 fn despawn_network_menu(mut commands: Commands, query: Query<Entity, With<NetworkRoot>>) {
     for e in query {
         commands.entity(e).despawn();
@@ -599,14 +1014,18 @@ fn process_loading_fade_out(
     }
 }
 
-fn leave_loading_on_u(mut state: ResMut<NextState<AppState>>, keys: Res<ButtonInput<KeyCode>>) {
-    if keys.just_pressed(KeyCode::KeyU) {
+fn leave_loading_on_u(
+    mut state: ResMut<NextState<AppState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    app: Res<Settings>,
+) {
+    if app.bindings.just_pressed(Action::LeaveLoading, &keys) {
         state.set(AppState::Menu);
     }
 }
 
-fn send_text_on_key_r(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>) {
-    if keys.just_pressed(KeyCode::KeyR) {
+fn send_text_on_key_r(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>, app: Res<Settings>) {
+    if app.bindings.just_pressed(Action::LoadingText, &keys) {
         let s = format!("{:?}", std::time::Instant::now());
         commands.write_message(LoadingMessage(s));
     }
@@ -0,0 +1,310 @@
+use std::process::ExitCode;
+use std::sync::atomic::AtomicU64;
+
+use vertex::*;
+
+const USAGE: &str = "\
+Manage a vertex installation without starting the game.
+
+Usage: updater [--install <dir>] [--source <source>] [--trust] <command>
+
+Commands:
+    sync [--force]          fetch the manifest and every pack it lists, and
+                            install new or changed puzzles, or every puzzle
+                            with --force
+    list                    show the puzzles in every installed pack
+    verify                  check that every listed puzzle is installed and loads
+    reinstall <puzzle>      fetch a fresh copy of one puzzle, named as
+                            <pack>/<short_name>, or just <short_name> for
+                            the main pack
+    prune [--dry-run]       delete installed puzzles no longer in a manifest
+
+Options:
+    --install <dir>         installation directory (default ~/.vertex_install)
+    --source <source>       where puzzles are fetched from; either a base url,
+                            or a local directory laid out the same way, with
+                            or without file:// (default from settings.yaml)
+    --trust                 install the source's manifest even if it isn't
+                            signed with the configured key
+";
+
+enum Command {
+    Sync { force: bool },
+    List,
+    Verify,
+    Reinstall(String),
+    Prune { dry_run: bool },
+}
+
+struct Args {
+    install: PathBuf,
+    source: Option<String>,
+    trust: bool,
+    command: Command,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut install = Installation::default_root();
+    let mut source = None;
+    let mut words = Vec::new();
+    let mut flags = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--install" => {
+                install = args
+                    .next()
+                    .ok_or("Expected a directory after --install")?
+                    .into()
+            }
+            "--source" => source = Some(args.next().ok_or("Expected a source after --source")?),
+            s if s.starts_with("--") => flags.push(arg),
+            _ => words.push(arg),
+        }
+    }
+
+    let flag = |name: &str| flags.iter().any(|f| f == name);
+
+    let command = match words.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["sync"] => Command::Sync {
+            force: flag("--force"),
+        },
+        ["list"] => Command::List,
+        ["verify"] => Command::Verify,
+        ["reinstall", name] => Command::Reinstall(name.to_string()),
+        ["prune"] => Command::Prune {
+            dry_run: flag("--dry-run"),
+        },
+        [] => return Err("Expected a command".to_string()),
+        _ => return Err(format!("Unrecognized command \"{}\"", words.join(" "))),
+    };
+
+    let known = ["--force", "--dry-run", "--trust"];
+    if let Some(f) = flags.iter().find(|f| !known.contains(&f.as_str())) {
+        return Err(format!("Unrecognized option \"{}\"", f));
+    }
+
+    Ok(Args {
+        install,
+        source,
+        trust: flag("--trust"),
+        command,
+    })
+}
+
+fn sync(
+    install: &Installation,
+    source: &dyn PuzzleSource,
+    verifier: &ManifestVerifier,
+    force: bool,
+) -> Result<(), VertexError> {
+    let report = sync_packs(install, source, verifier, force, &SyncProgress::default())?;
+
+    for name in &report.added {
+        println!("{:<24} added", name);
+    }
+    for name in &report.updated {
+        println!("{:<24} updated", name);
+    }
+    for name in &report.removed {
+        println!("{:<24} removed from manifest", name);
+    }
+    for (name, e) in &report.failed {
+        println!("{:<24} failed: {}", name, e);
+    }
+
+    println!("{}", report.summary());
+
+    match report.failure_summary() {
+        Some(s) => Err(VertexError::Str(s)),
+        None => Ok(()),
+    }
+}
+
+fn list(install: &Installation) -> Result<(), VertexError> {
+    let packs = PuzzlePacks::from_install(install)?;
+
+    for pack in &packs.0 {
+        let (complete, total) = pack_progress(install, pack);
+        println!("{} ({} of {} complete)", pack.title, complete, total);
+
+        let pack_install = install.pack(&pack.name);
+        for info in &pack.manifest.puzzles {
+            let key = PuzzleKey::new(&pack.name, &info.short_name);
+            let status = if !std::fs::exists(pack_install.puzzle_file(&info.short_name))? {
+                "not installed".to_string()
+            } else {
+                let save = SaveData::from_file(&pack_install.save_data_file(&info.short_name))?;
+                if save.was_ever_complete {
+                    "complete".to_string()
+                } else {
+                    format!("{} edges placed", save.edges.0.len())
+                }
+            };
+            println!("    {:<24} {:<32} {}", key.to_string(), info.title, status);
+        }
+    }
+
+    Ok(())
+}
+
+// whether an installed puzzle is present, matches the manifest and loads
+fn check_puzzle(install: &Installation, info: &PuzzleManifestInfo) -> Result<(), VertexError> {
+    let path = install.puzzle_file(&info.short_name);
+    if !std::fs::exists(&path)? {
+        return Err("Not installed".into());
+    }
+    let size = std::fs::metadata(&path)?.len();
+    if size > info.size_limit() {
+        return Err(IntegrityError::too_large(
+            &info.short_name,
+            info.size_limit(),
+        ));
+    }
+    if let Some(expected) = &info.hash {
+        check_hash(&info.short_name, &path, expected)?;
+    }
+    puzzle_from_file(&path)?;
+    Ok(())
+}
+
+fn verify(install: &Installation) -> Result<(), VertexError> {
+    let packs = PuzzlePacks::from_install(install)?;
+
+    let mut bad = 0;
+    let mut total = 0;
+    for pack in &packs.0 {
+        let pack_install = install.pack(&pack.name);
+        for info in &pack.manifest.puzzles {
+            let key = PuzzleKey::new(&pack.name, &info.short_name);
+            total += 1;
+            match check_puzzle(&pack_install, info) {
+                Ok(()) => println!("{:<24} ok", key.to_string()),
+                Err(e) => {
+                    println!("{:<24} {}", key.to_string(), e);
+                    bad += 1;
+                }
+            }
+        }
+    }
+
+    if bad > 0 {
+        return Err(VertexError::Str(format!(
+            "{} of {} puzzles have problems; `updater reinstall <puzzle>` may fix them",
+            bad, total
+        )));
+    }
+
+    println!("All {} puzzles ok", total);
+    Ok(())
+}
+
+fn reinstall(
+    install: &Installation,
+    source: &dyn PuzzleSource,
+    key: &PuzzleKey,
+) -> Result<(), VertexError> {
+    let packs = PuzzlePacks::from_install(install)?;
+    let Some(info) = packs.get(key) else {
+        return Err(VertexError::Str(format!(
+            "\"{}\" isn't in any installed manifest; try `updater sync` first",
+            key
+        )));
+    };
+
+    let received = AtomicU64::default();
+    let bytes = if key.pack == DEFAULT_PACK {
+        install_puzzle_verified(install, source, info, &received)?
+    } else {
+        let root = Manifest::from_file(&install.network_manifest())?;
+        let pack = root
+            .packs
+            .iter()
+            .find(|p| p.name == key.pack)
+            .ok_or("The pack is no longer listed by the source")?;
+        let cache = FetchCache::new(install.fetch_cache());
+        let source = pack_source(source, pack, Some(cache));
+        install_puzzle_verified(&install.pack(&key.pack), source.as_ref(), info, &received)?
+    };
+
+    println!("Reinstalled {} ({} bytes)", key, bytes);
+    Ok(())
+}
+
+fn prune(install: &Installation, dry_run: bool) -> Result<(), VertexError> {
+    let packs = PuzzlePacks::from_install(install)?;
+
+    let mut pruned = 0;
+    for pack in &packs.0 {
+        let listed: HashSet<&str> = pack
+            .manifest
+            .puzzles
+            .iter()
+            .map(|p| p.short_name.as_str())
+            .collect();
+
+        for entry in std::fs::read_dir(install.pack(&pack.name).puzzles())? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if listed.contains(name.as_str()) || !entry.file_type()?.is_dir() {
+                continue;
+            }
+
+            if dry_run {
+                println!("Would remove {}", entry.path().display());
+            } else {
+                std::fs::remove_dir_all(entry.path())?;
+                println!("Removed {}", entry.path().display());
+            }
+            pruned += 1;
+        }
+    }
+
+    println!("{} puzzles not in the manifest", pruned);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = Installation::initialize(args.install).and_then(|install| {
+        let settings = match Settings::from_file(&install.settings()) {
+            Ok(settings) => settings,
+            Err(e) => {
+                eprintln!("Failed to load settings, using defaults: {}", e);
+                Settings::default()
+            }
+        };
+        let source_name = args.source.unwrap_or(settings.puzzle_source.clone());
+        let source = parse_source(&source_name, Some(FetchCache::new(install.fetch_cache())));
+        let verifier = match args.trust {
+            true => ManifestVerifier::trusted(),
+            false => ManifestVerifier::for_source(&settings, &source_name),
+        };
+
+        match args.command {
+            Command::Sync { force } => sync(&install, source.as_ref(), &verifier, force),
+            Command::List => list(&install),
+            Command::Verify => verify(&install),
+            Command::Reinstall(name) => {
+                reinstall(&install, source.as_ref(), &PuzzleKey::parse(&name))
+            }
+            Command::Prune { dry_run } => prune(&install, dry_run),
+        }
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}